@@ -0,0 +1,3 @@
+CREATE INDEX IF NOT EXISTS notes_created_id_idx ON notes(created_at, id);
+CREATE INDEX IF NOT EXISTS follows_followee_created_idx ON follows(followee_id, created_at);
+CREATE INDEX IF NOT EXISTS follows_follower_created_idx ON follows(follower_id, created_at);
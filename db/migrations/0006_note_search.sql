@@ -0,0 +1,14 @@
+CREATE OR REPLACE FUNCTION note_search_vector(value BYTEA)
+    RETURNS TSVECTOR
+    LANGUAGE SQL
+    IMMUTABLE
+    PARALLEL SAFE
+AS $$
+    SELECT to_tsvector('simple'::regconfig, convert_from(value, 'UTF8'))
+$$;
+
+ALTER TABLE notes
+    ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (note_search_vector(value)) STORED;
+
+CREATE INDEX IF NOT EXISTS notes_search_idx ON notes USING GIN (search_vector);
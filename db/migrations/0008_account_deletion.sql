@@ -0,0 +1,5 @@
+ALTER TABLE users
+    ALTER COLUMN google_sub DROP NOT NULL;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS id UUID NOT NULL DEFAULT uuid_generate_v4();
+
+CREATE UNIQUE INDEX IF NOT EXISTS sessions_id_idx ON sessions(id);
@@ -0,0 +1,9 @@
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS token_hash BYTEA;
+
+UPDATE sessions SET token_hash = sha256(convert_to(token, 'UTF8')) WHERE token_hash IS NULL;
+
+ALTER TABLE sessions DROP CONSTRAINT IF EXISTS sessions_pkey;
+ALTER TABLE sessions DROP COLUMN IF EXISTS token;
+ALTER TABLE sessions ALTER COLUMN token_hash SET NOT NULL;
+ALTER TABLE sessions ADD PRIMARY KEY (token_hash);
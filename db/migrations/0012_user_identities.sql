@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS user_identities (
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS user_identities_user_provider_idx ON user_identities(user_id, provider);
+
+INSERT INTO user_identities (provider, subject, user_id, email, created_at)
+SELECT 'google', google_sub, user_id, email, created_at
+FROM users
+WHERE google_sub IS NOT NULL
+ON CONFLICT (provider, subject) DO NOTHING;
+
+ALTER TABLE users DROP COLUMN IF EXISTS google_sub;
@@ -0,0 +1,15 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS handle TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS display_name TEXT;
+
+UPDATE users SET handle = 'user_' || substr(replace(user_id::text, '-', ''), 1, 12)
+WHERE handle IS NULL;
+
+ALTER TABLE users ALTER COLUMN handle SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS users_handle_idx ON users(handle);
+
+-- Auto-generated account notes embedded the sign-in email; rewrite them to the handle.
+UPDATE notes n SET value = convert_to('account: @' || u.handle, 'UTF8')
+FROM users u
+WHERE n.id = u.account_note_id
+  AND u.email <> ''
+  AND n.value = convert_to('account: ' || u.email, 'UTF8');
@@ -0,0 +1,31 @@
+-- Earlier account notes were orphaned when POST /account/note replaced them.
+-- Their author associations identify them; chain them into version history.
+WITH history AS (
+    SELECT DISTINCT n.id, n.author_id, n.created_at
+    FROM associations a
+    JOIN notes n ON n.id = a.from_id
+    JOIN users u ON u.user_id = n.author_id
+    WHERE a.kind = 'author' AND a.from_id IS DISTINCT FROM u.account_note_id
+    UNION
+    SELECT n.id, n.author_id, n.created_at
+    FROM users u
+    JOIN notes n ON n.id = u.account_note_id
+), ordered AS (
+    SELECT id, LEAD(id) OVER (PARTITION BY author_id ORDER BY created_at, id) AS next_id
+    FROM history
+)
+INSERT INTO associations (id, kind, from_id, to_id, created_at)
+SELECT uuid_generate_v4(), 'version', o.id, o.next_id, NOW()
+FROM ordered o
+WHERE o.next_id IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM associations a WHERE a.kind = 'version' AND a.from_id = o.id);
+
+-- author associations always hang off the current account note.
+UPDATE associations a
+SET from_id = u.account_note_id
+FROM notes n
+JOIN users u ON u.user_id = n.author_id
+WHERE a.kind = 'author'
+  AND n.id = a.from_id
+  AND u.account_note_id IS NOT NULL
+  AND a.from_id <> u.account_note_id;
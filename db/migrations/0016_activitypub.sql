@@ -0,0 +1,46 @@
+-- Per-user RSA key pairs for ActivityPub HTTP signatures, created on first use.
+CREATE TABLE IF NOT EXISTS actor_keys (
+    user_id UUID PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    public_key_pem TEXT NOT NULL,
+    private_key_pem TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Actors on other servers, cached from their actor documents.
+CREATE TABLE IF NOT EXISTS remote_actors (
+    id UUID PRIMARY KEY,
+    actor_uri TEXT NOT NULL UNIQUE,
+    inbox_url TEXT NOT NULL,
+    shared_inbox_url TEXT,
+    key_id TEXT NOT NULL,
+    public_key_pem TEXT NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS remote_actors_key_id_idx ON remote_actors(key_id);
+
+-- A follow now comes from either a local user or a remote actor.
+ALTER TABLE follows DROP CONSTRAINT IF EXISTS follows_pkey;
+ALTER TABLE follows ALTER COLUMN follower_id DROP NOT NULL;
+ALTER TABLE follows ADD COLUMN IF NOT EXISTS remote_follower_id UUID REFERENCES remote_actors(id) ON DELETE CASCADE;
+ALTER TABLE follows ADD COLUMN IF NOT EXISTS remote_activity_id TEXT;
+ALTER TABLE follows ADD CONSTRAINT follows_one_follower CHECK ((follower_id IS NULL) <> (remote_follower_id IS NULL));
+ALTER TABLE follows ADD CONSTRAINT follows_local_key UNIQUE (follower_id, followee_id);
+ALTER TABLE follows ADD CONSTRAINT follows_remote_key UNIQUE (remote_follower_id, followee_id);
+
+-- Outbox for ActivityPub deliveries; activities are signed when sent so the Date header stays fresh.
+CREATE TABLE IF NOT EXISTS federation_deliveries (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    inbox_url TEXT NOT NULL,
+    activity JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_status_code INT,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS federation_deliveries_due_idx ON federation_deliveries(next_attempt_at) WHERE status = 'pending';
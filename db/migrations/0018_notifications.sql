@@ -0,0 +1,14 @@
+-- Per-user inbox of replies, quotes, follows and new versions of replied-to notes.
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('reply', 'quote', 'follow', 'version')),
+    actor_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    note_id BYTEA,
+    target_id BYTEA,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_idx ON notifications(user_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS notifications_unread_idx ON notifications(user_id) WHERE read_at IS NULL;
@@ -0,0 +1,2 @@
+-- Reply and quote threads are walked from the note being answered to its answers.
+CREATE INDEX IF NOT EXISTS associations_to_kind_idx ON associations(to_id, kind, created_at);
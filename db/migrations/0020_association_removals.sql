@@ -0,0 +1,14 @@
+-- Audit trail of associations removed by the source note's owner.
+CREATE TABLE IF NOT EXISTS association_removals (
+    id UUID PRIMARY KEY,
+    association_id UUID NOT NULL,
+    kind TEXT NOT NULL,
+    from_id BYTEA NOT NULL REFERENCES notes(id),
+    to_id BYTEA NOT NULL REFERENCES notes(id),
+    created_at TIMESTAMPTZ NOT NULL,
+    removed_by UUID NOT NULL REFERENCES users(user_id),
+    removed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS association_removals_from_idx ON association_removals(from_id, removed_at);
+CREATE INDEX IF NOT EXISTS association_removals_to_idx ON association_removals(to_id, removed_at);
@@ -1,21 +1,42 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::http::header;
+use actix_web::web::Bytes;
 use actix_web::{web, HttpRequest, HttpResponse};
 use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::{mpsc, oneshot};
 
-use crate::api::helpers::{parse_json, require_user};
+use crate::api::helpers::{parse_json, parse_query, parse_query_param, require_user};
+use crate::auth::api_tokens::Scope;
+use crate::domain::{is_reserved_handle, normalize_handle, ExportRecord};
 use crate::errors::ApiError;
 use crate::state::AppState;
+use crate::storage::ProfileUpdate;
+
+/// Records buffered between the export cursors and a slow client.
+const EXPORT_BUFFER: usize = 256;
 
 #[derive(Deserialize)]
 struct AccountNoteRequest {
     value: String,
 }
 
+#[derive(Deserialize)]
+struct ProfileRequest {
+    handle: Option<String>,
+    display_name: Option<String>,
+}
+
 pub async fn post_account_note(
     req: HttpRequest,
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let user = require_user(&req, &state).await?;
+    let user = require_user(&req, &state, Scope::Account).await?;
     let payload: AccountNoteRequest = parse_json(body.as_ref())?;
     let bytes = payload.value.as_bytes();
     if bytes.len() > 1024 {
@@ -28,9 +49,251 @@ pub async fn post_account_note(
 
     let note = state
         .storage
-        .create_account_note(user.user_id, bytes)
+        .replace_account_note(user.user_id, bytes)
         .await
         .map_err(|_| ApiError::internal())?;
 
     Ok(HttpResponse::Created().json(note))
 }
+
+pub async fn get_account_export(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let params = parse_query(&req);
+    let format = parse_query_param(&params, "format").unwrap_or("json");
+    if format != "json" && format != "ndjson" {
+        return Err(ApiError::bad_request(
+            "invalid_format",
+            "Format must be json or ndjson",
+            None,
+        ));
+    }
+
+    let (records, mut receiver) = mpsc::channel(EXPORT_BUFFER);
+    let (outcome, finished) = oneshot::channel();
+    let storage = state.storage.clone();
+    let export_user = user.clone();
+    tokio::spawn(async move {
+        let result = storage.export_account(&export_user, &records).await;
+        if let Err(err) = &result {
+            log::error!("account export for {} failed: {err}", export_user.user_id);
+        }
+        // Report before the channel closes, so the body knows how the export ended.
+        outcome.send(result.is_ok()).ok();
+        drop(records);
+    });
+    // The profile always comes first; without it nothing has been sent yet and a 500 still fits.
+    let first = receiver.recv().await.ok_or_else(ApiError::internal)?;
+
+    let disposition = format!("attachment; filename=\"lgxpkf-export-{}.{format}\"", user.user_id);
+    let content_type = if format == "ndjson" { "application/x-ndjson" } else { "application/json" };
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((header::CONTENT_DISPOSITION, disposition))
+        .body(ExportBody {
+            first: Some(first),
+            records: receiver,
+            finished,
+            document: (format == "json").then(JsonDocument::default),
+            done: false,
+        }))
+}
+
+pub async fn patch_account(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let payload: ProfileRequest = parse_json(body.as_ref())?;
+
+    let handle = match payload.handle.as_deref() {
+        Some(value) => Some(normalize_handle(value).ok_or_else(|| {
+            ApiError::unprocessable(
+                "invalid_handle",
+                "Handle must be 3-30 characters of a-z, 0-9 or _",
+                None,
+            )
+        })?),
+        None => None,
+    };
+    if handle.as_deref().is_some_and(|handle| is_reserved_handle(handle, user.user_id)) {
+        return Err(ApiError::unprocessable(
+            "handle_reserved",
+            "Handles starting with user_ are reserved",
+            None,
+        ));
+    }
+    let display_name = payload.display_name.as_deref().map(str::trim);
+    if display_name.is_some_and(|name| name.chars().count() > 64 || name.chars().any(char::is_control)) {
+        return Err(ApiError::unprocessable(
+            "invalid_display_name",
+            "Display name must be at most 64 printable characters",
+            None,
+        ));
+    }
+
+    let outcome = state
+        .storage
+        .update_profile(user.user_id, handle.as_deref(), display_name)
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    match outcome {
+        Some(ProfileUpdate::Updated(user)) => {
+            Ok(HttpResponse::Ok().json(json!({"user": user.account_profile()})))
+        }
+        Some(ProfileUpdate::HandleTaken) => {
+            Err(ApiError::conflict("handle_taken", "Handle is already taken"))
+        }
+        None => Err(ApiError::unauthorized("unauthorized", "Invalid session")),
+    }
+}
+
+pub async fn delete_account(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let retracted = state
+        .storage
+        .delete_account(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "deleted",
+        "user_id": user.user_id,
+        "retracted_notes": retracted,
+    })))
+}
+
+/// Response body for GET /account/export, fed by the task reading the export cursors. A failed
+/// export errors the body, so the client sees a cut-off transfer rather than a short file.
+struct ExportBody {
+    first: Option<ExportRecord>,
+    records: mpsc::Receiver<ExportRecord>,
+    finished: oneshot::Receiver<bool>,
+    /// `None` writes ndjson.
+    document: Option<JsonDocument>,
+    done: bool,
+}
+
+impl ExportBody {
+    fn encode(&mut self, record: &ExportRecord) -> Result<Bytes, io::Error> {
+        let chunk = match &mut self.document {
+            Some(document) => document.push(record)?,
+            None => serde_json::to_string(record)? + "\n",
+        };
+        Ok(Bytes::from(chunk))
+    }
+}
+
+impl MessageBody for ExportBody {
+    type Error = io::Error;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = &mut *self;
+        if this.done {
+            return Poll::Ready(None);
+        }
+        if let Some(first) = this.first.take() {
+            return Poll::Ready(Some(this.encode(&first)));
+        }
+        match ready!(this.records.poll_recv(cx)) {
+            Some(record) => Poll::Ready(Some(this.encode(&record))),
+            None => {
+                this.done = true;
+                if this.finished.try_recv() != Ok(true) {
+                    return Poll::Ready(Some(Err(io::Error::other("account export failed"))));
+                }
+                Poll::Ready(this.document.as_mut().map(|document| Ok(Bytes::from(document.finish()))))
+            }
+        }
+    }
+}
+
+/// Sections of the JSON export after the profile, in the order storage emits them.
+const JSON_SECTIONS: [&str; 7] = [
+    "identities",
+    "account_note",
+    "notes",
+    "associations",
+    "followers",
+    "remote_followers",
+    "following",
+];
+const ACCOUNT_NOTE_SECTION: usize = 1;
+
+/// Writes export records as one JSON object, opening each section as its first record arrives and
+/// filling in sections that had none.
+#[derive(Default)]
+struct JsonDocument {
+    next: usize,
+    open_array: bool,
+}
+
+impl JsonDocument {
+    fn push(&mut self, record: &ExportRecord) -> serde_json::Result<String> {
+        let (section, data) = match record {
+            ExportRecord::Profile { profile, created_at } => {
+                return Ok(format!(
+                    "{{\"profile\":{},\"created_at\":{}",
+                    serde_json::to_string(profile)?,
+                    serde_json::to_string(created_at)?
+                ));
+            }
+            ExportRecord::Identity(identity) => (0, serde_json::to_string(identity)?),
+            ExportRecord::AccountNote(note) => (ACCOUNT_NOTE_SECTION, serde_json::to_string(note)?),
+            ExportRecord::Note(note) => (2, serde_json::to_string(note)?),
+            ExportRecord::Association(association) => (3, serde_json::to_string(association)?),
+            ExportRecord::Follower(edge) => (4, serde_json::to_string(edge)?),
+            ExportRecord::RemoteFollower(follower) => (5, serde_json::to_string(follower)?),
+            ExportRecord::Following(edge) => (6, serde_json::to_string(edge)?),
+        };
+        let mut chunk = if self.open_array && self.next == section + 1 {
+            ",".to_string()
+        } else {
+            let mut chunk = self.close_until(section);
+            chunk.push_str(&format!(",\"{}\":", JSON_SECTIONS[section]));
+            self.next = section + 1;
+            if section != ACCOUNT_NOTE_SECTION {
+                chunk.push('[');
+                self.open_array = true;
+            }
+            chunk
+        };
+        chunk.push_str(&data);
+        Ok(chunk)
+    }
+
+    fn finish(&mut self) -> String {
+        let mut chunk = self.close_until(JSON_SECTIONS.len());
+        chunk.push('}');
+        chunk
+    }
+
+    /// Closes the open array and writes empty values for the skipped sections before `section`.
+    fn close_until(&mut self, section: usize) -> String {
+        let mut chunk = String::new();
+        if self.open_array {
+            chunk.push(']');
+            self.open_array = false;
+        }
+        while self.next < section {
+            let empty = if self.next == ACCOUNT_NOTE_SECTION { "null" } else { "[]" };
+            chunk.push_str(&format!(",\"{}\":{empty}", JSON_SECTIONS[self.next]));
+            self.next += 1;
+        }
+        chunk
+    }
+}
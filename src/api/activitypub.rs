@@ -0,0 +1,635 @@
+use std::time::{Duration, SystemTime};
+
+use actix_web::guard::GuardContext;
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::api::helpers::{parse_json, parse_note_id, parse_query, parse_query_param, parse_uuid};
+use crate::api::pagination::{note_cursor, notes_page, parse_cursor_param};
+use crate::domain::note::format_timestamp;
+use crate::domain::{normalize_handle, Note, NoteId, RemoteActor, User};
+use crate::errors::ApiError;
+use crate::federation::activities::{
+    accept_follow, actor_document, actor_uri, note_object, note_uri, ordered_collection,
+    ordered_collection_page, webfinger, wrap_object,
+};
+use crate::federation::keys::ensure_actor_key;
+use crate::federation::signatures::{digest_header, parse_signature_header, signing_string, verify};
+use crate::federation::ACTIVITY_JSON;
+use crate::related::fetch_chain;
+use crate::state::AppState;
+use crate::web::{post_html, render_markdown};
+
+const OUTBOX_PAGE_SIZE: usize = 20;
+/// Mastodon's window; remote clocks and delivery retries make anything tighter brittle.
+const MAX_SIGNATURE_SKEW: Duration = Duration::from_secs(12 * 3600);
+const REQUIRED_SIGNED_HEADERS: &[&str] = &["(request-target)", "host", "date", "digest"];
+
+/// Which activity a post is delivered as.
+#[derive(Clone, Copy)]
+pub enum Delivery {
+    Create,
+    Update,
+}
+
+/// Route guard for ActivityPub clients asking for the JSON-LD form of a resource.
+pub fn accepts_activity(ctx: &GuardContext) -> bool {
+    let enabled = ctx
+        .app_data::<web::Data<AppState>>()
+        .is_some_and(|state| state.config.activitypub_enabled);
+    enabled
+        && ctx
+            .head()
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value.contains("application/activity+json") || value.contains("application/ld+json")
+            })
+}
+
+pub async fn get_webfinger(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    require_enabled(&state)?;
+    let params = parse_query(&req);
+    let resource = parse_query_param(&params, "resource")
+        .ok_or_else(|| ApiError::bad_request("invalid_resource", "Missing resource", None))?;
+    let host = public_host(&state)?;
+    let handle = resource
+        .strip_prefix("acct:")
+        .and_then(|acct| acct.rsplit_once('@'))
+        .filter(|(_, domain)| domain.eq_ignore_ascii_case(&host))
+        .map(|(handle, _)| handle.trim_start_matches('@'))
+        .ok_or_else(|| ApiError::bad_request("invalid_resource", "Unsupported resource", None))?;
+    let handle = normalize_handle(handle)
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+    let user = state
+        .storage
+        .find_user_by_handle(&handle)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+    let body = webfinger(&state.config.public_base_url, &host, &user.profile());
+    Ok(HttpResponse::Ok()
+        .content_type("application/jrd+json")
+        .body(body.to_string()))
+}
+
+pub async fn get_actor(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    require_enabled(&state)?;
+    let user = load_user(&state, path.as_str()).await?;
+    let key = ensure_actor_key(&state.storage, user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let summary = match user.account_note_id.as_deref() {
+        Some(raw) => state
+            .storage
+            .find_note(parse_note_id(raw)?)
+            .await
+            .map_err(|_| ApiError::internal())?
+            .filter(|note| !note.is_retracted())
+            .map(|note| render_markdown(&note.value)),
+        None => None,
+    };
+    let body = actor_document(
+        &state.config.public_base_url,
+        &user.profile(),
+        summary.as_deref(),
+        &key.public_key_pem,
+    );
+    Ok(activity_response(&body))
+}
+
+/// Without `page` this is the collection summary; `?page=true` walks chain heads newest first.
+pub async fn get_outbox(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    require_enabled(&state)?;
+    let user = load_user(&state, path.as_str()).await?;
+    let outbox = format!("{}/outbox", actor_uri(&state.config.public_base_url, user.user_id));
+    let params = parse_query(&req);
+    if parse_query_param(&params, "page").is_none() {
+        let first = format!("{outbox}?page=true");
+        return Ok(activity_response(&ordered_collection(&outbox, None, Some(&first))));
+    }
+    let raw_cursor = parse_query_param(&params, "cursor");
+    let cursor = note_cursor(parse_cursor_param(&params, "cursor")?)?;
+    let notes = state
+        .storage
+        .list_author_posts(user.user_id, cursor, OUTBOX_PAGE_SIZE as i64 + 1)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let page = notes_page(notes, OUTBOX_PAGE_SIZE);
+    let mut items = Vec::with_capacity(page.items.len());
+    for note in &page.items {
+        let object = post_object(&state, note).await?;
+        items.push(wrap_object("Create", object, &note.created_at));
+    }
+    let id = match raw_cursor {
+        Some(cursor) => format!("{outbox}?page=true&cursor={cursor}"),
+        None => format!("{outbox}?page=true"),
+    };
+    let next = page
+        .next_cursor
+        .as_deref()
+        .map(|cursor| format!("{outbox}?page=true&cursor={cursor}"));
+    Ok(activity_response(&ordered_collection_page(&id, &outbox, items, next.as_deref())))
+}
+
+/// Followers are exposed as a count only.
+pub async fn get_followers(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    require_enabled(&state)?;
+    let user = load_user(&state, path.as_str()).await?;
+    let (local, _) = state
+        .storage
+        .count_follows(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let remote = state
+        .storage
+        .count_remote_followers(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let id = format!("{}/followers", actor_uri(&state.config.public_base_url, user.user_id));
+    Ok(activity_response(&ordered_collection(&id, Some(local + remote), None)))
+}
+
+/// The Note object for the post containing a note; chain segments resolve to their head.
+pub async fn get_note_object(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let note_id = parse_note_id(path.as_str())?;
+    let note = state
+        .storage
+        .find_note(note_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .filter(|note| !note.is_retracted())
+        .ok_or_else(|| ApiError::not_found("note_not_found", "Note not found"))?;
+    let mut object = post_object(&state, &note).await?;
+    object["@context"] = Value::from("https://www.w3.org/ns/activitystreams");
+    Ok(activity_response(&object))
+}
+
+/// Accepts signed Follow and Undo(Follow) activities; anything else is acknowledged and dropped.
+pub async fn post_inbox(
+    path: web::Path<String>,
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    require_enabled(&state)?;
+    let user = load_user(&state, path.as_str()).await?;
+    let activity: Value = parse_json(body.as_ref())?;
+    let remote = verify_request(&req, body.as_ref(), &state).await?;
+    if object_id(&activity["actor"]) != Some(remote.actor_uri.as_str()) {
+        return Err(ApiError::unauthorized(
+            "invalid_signature",
+            "Signature does not match the activity actor",
+        ));
+    }
+    let local_actor = actor_uri(&state.config.public_base_url, user.user_id);
+    match activity["type"].as_str() {
+        Some("Follow") => {
+            if object_id(&activity["object"]) != Some(local_actor.as_str()) {
+                return Err(ApiError::unprocessable(
+                    "invalid_activity",
+                    "Follow does not target this actor",
+                    None,
+                ));
+            }
+            state
+                .storage
+                .create_remote_follow(remote.id, user.user_id, activity["id"].as_str())
+                .await
+                .map_err(|_| ApiError::internal())?;
+            let accept = accept_follow(&local_actor, &activity);
+            state
+                .storage
+                .enqueue_federation_deliveries(user.user_id, &[remote.inbox_url], &accept)
+                .await
+                .map_err(|_| ApiError::internal())?;
+        }
+        Some("Undo") => {
+            let inner = &activity["object"];
+            let activity_id = match inner {
+                Value::String(id) => Some(id.as_str()),
+                _ if inner["type"] == "Follow"
+                    && object_id(&inner["object"]) == Some(local_actor.as_str()) =>
+                {
+                    None
+                }
+                _ => return Ok(HttpResponse::Accepted().finish()),
+            };
+            state
+                .storage
+                .delete_remote_follow(remote.id, user.user_id, activity_id)
+                .await
+                .map_err(|_| ApiError::internal())?;
+        }
+        _ => {}
+    }
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Queues a post for the author's remote followers. The post is already saved, so failures are
+/// logged rather than returned; once queued, the federation dispatcher retries sends.
+pub async fn queue_post_delivery(state: &AppState, author_id: Uuid, note_id: NoteId, delivery: Delivery) {
+    if !state.config.activitypub_enabled {
+        return;
+    }
+    if let Err(err) = enqueue_post(state, author_id, note_id, delivery).await {
+        log::warn!("activitypub delivery for {author_id} could not be queued: {err}");
+    }
+}
+
+async fn enqueue_post(
+    state: &AppState,
+    author_id: Uuid,
+    note_id: NoteId,
+    delivery: Delivery,
+) -> Result<(), String> {
+    let inboxes = state
+        .storage
+        .list_follower_inboxes(author_id)
+        .await
+        .map_err(|err| err.to_string())?;
+    if inboxes.is_empty() {
+        return Ok(());
+    }
+    let note = state
+        .storage
+        .find_note(note_id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or("note not found")?;
+    let object = post_object(state, &note).await.map_err(|err| err.to_string())?;
+    let (kind, published) = match delivery {
+        Delivery::Create => ("Create", note.created_at.clone()),
+        Delivery::Update => ("Update", format_timestamp(time::OffsetDateTime::now_utc())),
+    };
+    let activity = wrap_object(kind, object, &published);
+    state
+        .storage
+        .enqueue_federation_deliveries(author_id, &inboxes, &activity)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+/// Builds the Note object for the post a note heads, with `inReplyTo` from its newest `reply` association.
+async fn post_object(state: &AppState, note: &Note) -> Result<Value, ApiError<serde_json::Value>> {
+    let chain = fetch_chain(state, parse_note_id(&note.id)?).await?;
+    let head = chain.prev.first().unwrap_or(&chain.center);
+    let associations = state
+        .storage
+        .list_associations(parse_note_id(&head.id)?)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let base_url = &state.config.public_base_url;
+    let in_reply_to = associations
+        .iter()
+        .find(|assoc| assoc.kind == "reply" && assoc.from_id == head.id)
+        .map(|assoc| note_uri(base_url, &assoc.to_id));
+    Ok(note_object(base_url, head, &post_html(&chain), in_reply_to.as_deref()))
+}
+
+/// Checks the Digest, Date and Signature headers and returns the signing actor.
+/// Cached keys are tried first; a miss or mismatch refetches the key once, covering key rotation.
+async fn verify_request(
+    req: &HttpRequest,
+    body: &[u8],
+    state: &AppState,
+) -> Result<RemoteActor, ApiError<serde_json::Value>> {
+    let invalid = || ApiError::unauthorized("invalid_signature", "Invalid HTTP signature");
+    let params = req
+        .headers()
+        .get("signature")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_signature_header)
+        .ok_or_else(invalid)?;
+    if !REQUIRED_SIGNED_HEADERS
+        .iter()
+        .all(|name| params.headers.iter().any(|signed| signed == name))
+    {
+        return Err(invalid());
+    }
+    let expected_digest = digest_header(body);
+    let digest_ok = header_value(req, "digest").is_some_and(|value| {
+        value.split(',').any(|part| {
+            part.trim().split_once('=').is_some_and(|(alg, hash)| {
+                alg.eq_ignore_ascii_case("sha-256") && format!("SHA-256={hash}") == expected_digest
+            })
+        })
+    });
+    let date_ok = header_value(req, "date")
+        .and_then(|value| httpdate::parse_http_date(&value).ok())
+        .is_some_and(|date| {
+            let now = SystemTime::now();
+            let skew = now.duration_since(date).or_else(|_| date.duration_since(now));
+            skew.is_ok_and(|skew| skew <= MAX_SIGNATURE_SKEW)
+        });
+    if !digest_ok || !date_ok {
+        return Err(invalid());
+    }
+    let target = format!(
+        "{} {}",
+        req.method().as_str().to_ascii_lowercase(),
+        req.uri().path_and_query().map(|pq| pq.as_str()).unwrap_or("/")
+    );
+    let to_verify = signing_string(&params.headers, |name| match name {
+        "(request-target)" => Some(target.clone()),
+        _ => header_value(req, name),
+    })
+    .ok_or_else(invalid)?;
+
+    let cached = state
+        .storage
+        .find_remote_actor_by_key_id(&params.key_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if let Some(actor) = cached {
+        if verify(&actor.public_key_pem, &to_verify, &params.signature) {
+            return Ok(actor);
+        }
+    }
+    let document = state.federation.fetch_actor(&params.key_id).await.map_err(|err| {
+        log::info!("activitypub key fetch for {} failed: {err}", params.key_id);
+        invalid()
+    })?;
+    if !verify(&document.public_key_pem, &to_verify, &params.signature) {
+        return Err(invalid());
+    }
+    state
+        .storage
+        .upsert_remote_actor(
+            &document.id,
+            &document.inbox,
+            document.shared_inbox.as_deref(),
+            &document.key_id,
+            &document.public_key_pem,
+        )
+        .await
+        .map_err(|_| ApiError::internal())
+}
+
+async fn load_user(state: &AppState, raw_id: &str) -> Result<User, ApiError<serde_json::Value>> {
+    let user_id = parse_uuid(raw_id, "invalid_user_id", "Invalid user id")?;
+    state
+        .storage
+        .find_user_by_id(user_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))
+}
+
+fn require_enabled(state: &AppState) -> Result<(), ApiError<serde_json::Value>> {
+    if state.config.activitypub_enabled {
+        Ok(())
+    } else {
+        Err(ApiError::not_found("not_found", "Route not found"))
+    }
+}
+
+/// Host part of PUBLIC_BASE_URL, the domain in `acct:` handles.
+fn public_host(state: &AppState) -> Result<String, ApiError<serde_json::Value>> {
+    let url = url::Url::parse(&state.config.public_base_url).map_err(|_| ApiError::internal())?;
+    let host = url.host_str().ok_or_else(ApiError::internal)?;
+    Ok(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+/// An ActivityStreams reference is either a bare id or an embedded object with an `id`.
+fn object_id(value: &Value) -> Option<&str> {
+    value.as_str().or_else(|| value["id"].as_str())
+}
+
+fn header_value(req: &HttpRequest, name: &str) -> Option<String> {
+    let values: Vec<&str> = req
+        .headers()
+        .get_all(name)
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+fn activity_response(body: &Value) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ACTIVITY_JSON)
+        .body(body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use serde_json::{json, Value};
+    use tokio::sync::watch;
+    use uuid::Uuid;
+
+    use super::{post_inbox, queue_post_delivery, Delivery};
+    use crate::api::helpers::parse_note_id;
+    use crate::federation::activities::{actor_uri, key_id};
+    use crate::federation::keys::{ensure_actor_key, generate_actor_key};
+    use crate::federation::outbox::spawn_federation_dispatcher;
+    use crate::federation::signatures::{digest_header, parse_signature_header, signing_string, verify};
+    use crate::federation::ACTIVITY_JSON;
+    use crate::testing;
+
+    /// A POST the stub peer received.
+    struct Received {
+        path: String,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+    }
+
+    impl Received {
+        fn activity(&self) -> Value {
+            serde_json::from_slice(&self.body).unwrap()
+        }
+
+        /// Checks the request the way a remote server would: digest, then signature over the signed headers.
+        fn signed_by(&self, key_id: &str, public_key_pem: &str) -> bool {
+            let Some(params) = self.headers.get("signature").and_then(|value| parse_signature_header(value)) else {
+                return false;
+            };
+            let to_verify = signing_string(&params.headers, |name| match name {
+                "(request-target)" => Some(format!("post {}", self.path)),
+                _ => self.headers.get(name).cloned(),
+            });
+            params.key_id == key_id
+                && self.headers.get("digest") == Some(&digest_header(&self.body))
+                && to_verify.is_some_and(|to_verify| verify(public_key_pem, &to_verify, &params.signature))
+        }
+    }
+
+    /// Serves one remote actor on a loopback port and records every POST to it.
+    fn start_peer(public_key_pem: &str) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let actor = json!({
+            "id": format!("{base}/actor"),
+            "type": "Person",
+            "inbox": format!("{base}/inbox"),
+            "endpoints": { "sharedInbox": format!("{base}/shared") },
+            "publicKey": {
+                "id": format!("{base}/actor#main-key"),
+                "owner": format!("{base}/actor"),
+                "publicKeyPem": public_key_pem,
+            },
+        });
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let server = HttpServer::new(move || {
+            let actor = actor.clone();
+            let log = log.clone();
+            App::new()
+                .route(
+                    "/actor",
+                    web::get().to(move || {
+                        let actor = actor.clone();
+                        async move { HttpResponse::Ok().content_type(ACTIVITY_JSON).body(actor.to_string()) }
+                    }),
+                )
+                .default_service(web::post().to(move |req: HttpRequest, body: web::Bytes| {
+                    let headers = req
+                        .headers()
+                        .iter()
+                        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                        .collect();
+                    log.lock().unwrap().push(Received {
+                        path: req.path().to_string(),
+                        headers,
+                        body: body.to_vec(),
+                    });
+                    async { HttpResponse::Accepted().finish() }
+                }))
+        })
+        .workers(1)
+        .disable_signals()
+        .listen(listener)
+        .unwrap()
+        .run();
+        actix_web::rt::spawn(server);
+        (base, received)
+    }
+
+    #[actix_web::test]
+    async fn inbox_follow_undo_and_signed_delivery() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let Some(state) = testing::state(&base_url).await else {
+            return;
+        };
+        let app_state = state.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route("/users/{id}/inbox", web::post().to(post_inbox))
+        })
+        .workers(1)
+        .disable_signals()
+        .listen(listener)
+        .unwrap()
+        .run();
+        actix_web::rt::spawn(server);
+
+        let user = state
+            .storage
+            .find_or_create_user("test", &Uuid::new_v4().to_string(), "federation@example.com")
+            .await
+            .unwrap();
+        let local_actor = actor_uri(&base_url, user.user_id);
+        let inbox = format!("{local_actor}/inbox");
+        let peer_key = generate_actor_key().unwrap();
+        let (peer, received) = start_peer(&peer_key.public_key_pem);
+        let peer_actor = format!("{peer}/actor");
+        let peer_key_id = key_id(&peer_actor);
+        let follow = json!({
+            "id": format!("{peer}/follows/1"),
+            "type": "Follow",
+            "actor": peer_actor,
+            "object": local_actor,
+        });
+
+        // Signed with a key the peer does not publish.
+        let forged = generate_actor_key().unwrap();
+        let status = state.federation.deliver(&inbox, &peer_key_id, &forged.private_key_pem, &follow).await;
+        assert_eq!(status, Ok(401));
+        assert!(state.storage.list_follower_inboxes(user.user_id).await.unwrap().is_empty());
+
+        let status = state.federation.deliver(&inbox, &peer_key_id, &peer_key.private_key_pem, &follow).await;
+        assert_eq!(status, Ok(202));
+        assert_eq!(
+            state.storage.list_follower_inboxes(user.user_id).await.unwrap(),
+            vec![format!("{peer}/shared")]
+        );
+
+        let (note, _) = state
+            .storage
+            .create_note_chain(&[b"hello fediverse".to_vec()], user.user_id, &[])
+            .await
+            .unwrap();
+        let note_id = parse_note_id(&note.id).unwrap();
+        queue_post_delivery(&state, user.user_id, note_id, Delivery::Create).await;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let dispatcher = spawn_federation_dispatcher(
+            state.storage.clone(),
+            state.federation.clone(),
+            base_url.clone(),
+            1,
+            shutdown_rx,
+        );
+        let deadline = Instant::now() + Duration::from_secs(15);
+        while received.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        shutdown_tx.send(true).unwrap();
+        dispatcher.await.unwrap();
+
+        let local_key = ensure_actor_key(&state.storage, user.user_id).await.unwrap();
+        {
+            let received = received.lock().unwrap();
+            let accept = received.iter().find(|r| r.path == "/inbox").expect("Accept delivered");
+            assert_eq!(accept.activity()["type"], "Accept");
+            assert_eq!(accept.activity()["object"]["id"], follow["id"]);
+            let create = received.iter().find(|r| r.path == "/shared").expect("Create delivered");
+            assert_eq!(create.activity()["type"], "Create");
+            assert_eq!(create.activity()["actor"], local_actor.as_str());
+            assert_eq!(create.activity()["object"]["content"], "<p>hello fediverse</p>\n");
+            for request in received.iter() {
+                assert!(request.signed_by(&key_id(&local_actor), &local_key.public_key_pem));
+            }
+        }
+
+        let undo = json!({
+            "id": format!("{peer}/undo/1"),
+            "type": "Undo",
+            "actor": peer_actor,
+            "object": follow,
+        });
+        let status = state.federation.deliver(&inbox, &peer_key_id, &peer_key.private_key_pem, &undo).await;
+        assert_eq!(status, Ok(202));
+        assert!(state.storage.list_follower_inboxes(user.user_id).await.unwrap().is_empty());
+    }
+}
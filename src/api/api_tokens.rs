@@ -0,0 +1,116 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::api::helpers::{parse_json, parse_uuid, require_user};
+use crate::auth::api_tokens::{generate_api_token, Scope};
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+const MAX_NAME_CHARS: usize = 64;
+
+#[derive(Deserialize)]
+struct CreateTokenRequest {
+    name: String,
+    scopes: Vec<String>,
+    expires_at: Option<String>,
+}
+
+pub async fn get_api_tokens(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let tokens = state
+        .storage
+        .list_api_tokens(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"tokens": tokens})))
+}
+
+pub async fn post_api_tokens(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let payload: CreateTokenRequest = parse_json(body.as_ref())?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(ApiError::unprocessable(
+            "invalid_name",
+            "Token name must be 1-64 characters",
+            None,
+        ));
+    }
+
+    let mut scopes: Vec<String> = Vec::new();
+    for value in &payload.scopes {
+        let scope = Scope::parse_grantable(value).ok_or_else(|| {
+            ApiError::unprocessable(
+                "invalid_scope",
+                "Unknown scope",
+                Some(serde_json::json!({"scope": value})),
+            )
+        })?;
+        if !scopes.iter().any(|s| s == scope.as_str()) {
+            scopes.push(scope.as_str().to_string());
+        }
+    }
+    if scopes.is_empty() {
+        return Err(ApiError::unprocessable(
+            "invalid_scope",
+            "At least one scope is required",
+            None,
+        ));
+    }
+
+    let expires_at = match payload.expires_at.as_deref() {
+        Some(value) => {
+            let parsed = OffsetDateTime::parse(value, &Rfc3339).map_err(|_| {
+                ApiError::bad_request("invalid_timestamp", "Invalid timestamp", None)
+            })?;
+            if parsed <= OffsetDateTime::now_utc() {
+                return Err(ApiError::unprocessable(
+                    "invalid_expiry",
+                    "Expiry must be in the future",
+                    None,
+                ));
+            }
+            Some(parsed)
+        }
+        None => None,
+    };
+
+    let token = generate_api_token();
+    let created = state
+        .storage
+        .create_api_token(user.user_id, name, &token, &scopes, expires_at)
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    let mut response = serde_json::to_value(created).map_err(|_| ApiError::internal())?;
+    response["token"] = serde_json::Value::String(token);
+    Ok(HttpResponse::Created().json(response))
+}
+
+pub async fn delete_api_token(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let token_id = parse_uuid(path.as_str(), "invalid_token_id", "Invalid token id")?;
+    let deleted = state
+        .storage
+        .delete_api_token(user.user_id, token_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if !deleted {
+        return Err(ApiError::not_found("token_not_found", "Token not found"));
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"status": "revoked"})))
+}
@@ -1,9 +1,12 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use serde::Deserialize;
+use uuid::Uuid;
 
+use crate::api::activitypub::{queue_post_delivery, Delivery};
 use crate::api::helpers::{
     parse_json, parse_note_id, parse_note_reference, parse_query, parse_query_param, require_user,
 };
+use crate::auth::api_tokens::Scope;
 use crate::domain::{Note, User};
 use crate::errors::ApiError;
 use crate::state::AppState;
@@ -21,7 +24,7 @@ pub async fn post_associations(
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let user = require_user(&req, &state).await?;
+    let user = require_user(&req, &state, Scope::AssociationsWrite).await?;
     let payload: CreateAssociation = parse_json(body.as_ref())?;
     let kind = parse_kind(&payload.kind)?.to_ascii_lowercase();
     if !is_allowed_kind(&kind) {
@@ -73,9 +76,73 @@ pub async fn post_associations(
             }
             ApiError::internal()
         })?;
+    // Posts go out before their reply association exists; an Update carries the inReplyTo.
+    if kind == "reply" {
+        queue_post_delivery(&state, user.user_id, from_id, Delivery::Update).await;
+    }
     Ok(HttpResponse::Created().json(association))
 }
 
+/// Removes a user-created association from one of the caller's notes, keeping an audit record.
+pub async fn delete_associations(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::AssociationsWrite).await?;
+    let payload: CreateAssociation = parse_json(body.as_ref())?;
+    let kind = parse_kind(&payload.kind)?.to_ascii_lowercase();
+    if is_protected_kind(&kind) {
+        return Err(ApiError::unprocessable(
+            "association_protected",
+            "System associations cannot be removed",
+            None,
+        ));
+    }
+    let from_id = parse_note_id(&payload.from_id)?;
+    let to_id = parse_note_reference(&payload.to_id)?;
+
+    let from_note = state
+        .storage
+        .find_note(from_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("note_not_found", "Note not found"))?;
+    if from_note.author.user_id != user.user_id {
+        return Err(ApiError::forbidden(
+            "association_forbidden",
+            "Cannot remove associations from this note",
+        ));
+    }
+    if from_note.is_retracted() {
+        return Err(ApiError::unprocessable(
+            "note_retracted",
+            "Retracted notes cannot change associations",
+            None,
+        ));
+    }
+
+    let removals = state
+        .storage
+        .delete_association(&kind, from_id, to_id, user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if removals.is_empty() {
+        return Err(ApiError::not_found(
+            "association_not_found",
+            "Association not found",
+        ));
+    }
+    // Federated copies still carry the inReplyTo until an Update replaces them.
+    if kind == "reply" {
+        queue_post_delivery(&state, user.user_id, from_id, Delivery::Update).await;
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "deleted",
+        "removals": removals,
+    })))
+}
+
 pub async fn get_associations(
     req: HttpRequest,
     state: web::Data<AppState>,
@@ -93,6 +160,23 @@ pub async fn get_associations(
     Ok(HttpResponse::Ok().json(associations))
 }
 
+pub async fn get_association_removals(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let params = parse_query(&req);
+    let note_id = parse_query_param(&params, "note")
+        .ok_or_else(|| ApiError::bad_request("missing_note", "Missing note parameter", None))?;
+    let note_id = parse_note_id(note_id)?;
+
+    let removals = state
+        .storage
+        .list_association_removals(note_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(removals))
+}
+
 fn parse_kind(value: &str) -> Result<String, ApiError<serde_json::Value>> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -119,6 +203,11 @@ fn is_allowed_kind(kind: &str) -> bool {
     )
 }
 
+/// Kinds that make up a post's structure and history; only the server writes or drops them.
+fn is_protected_kind(kind: &str) -> bool {
+    matches!(kind, "author" | "next" | "version")
+}
+
 fn allows_cross_author(kind: &str) -> bool {
     matches!(kind, "link" | "reply" | "quote")
 }
@@ -148,6 +237,30 @@ fn ensure_association_allowed(
             None,
         ));
     }
+    if from_note.is_retracted() {
+        return Err(ApiError::unprocessable(
+            "note_retracted",
+            "Retracted notes cannot be associated",
+            None,
+        ));
+    }
+    ensure_association_target(kind, from_note.author.user_id, to_note, user)
+}
+
+/// The checks on the target alone, which also apply to the links a new post is created with.
+pub(crate) fn ensure_association_target(
+    kind: &str,
+    from_author: Uuid,
+    to_note: &Note,
+    user: &User,
+) -> Result<(), ApiError<serde_json::Value>> {
+    if to_note.is_retracted() {
+        return Err(ApiError::unprocessable(
+            "note_retracted",
+            "Retracted notes cannot be associated",
+            None,
+        ));
+    }
     if kind == "version" && is_account_note(user, to_note) {
         return Err(ApiError::unprocessable(
             "account_note_locked",
@@ -155,7 +268,7 @@ fn ensure_association_allowed(
             None,
         ));
     }
-    if !allows_cross_author(kind) && from_note.author.user_id != to_note.author.user_id {
+    if !allows_cross_author(kind) && from_author != to_note.author.user_id {
         return Err(ApiError::forbidden(
             "association_forbidden",
             "Cannot link notes owned by different authors",
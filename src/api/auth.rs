@@ -5,7 +5,9 @@ use std::collections::HashMap;
 use url::form_urlencoded;
 
 use crate::api::helpers::{parse_json, require_user};
-use crate::auth::{google, sessions};
+use crate::auth::api_tokens::Scope;
+use crate::auth::provider::Identity;
+use crate::auth::{google, oidc, sessions};
 use crate::errors::ApiError;
 use crate::state::AppState;
 use crate::web as web_views;
@@ -13,7 +15,7 @@ use crate::web as web_views;
 const POLICY_VERSION: &str = "2025-02-01";
 
 #[derive(Deserialize)]
-struct GoogleRequest {
+struct SignInRequest {
     id_token: String,
     policy_acceptance: Option<PolicyAcceptance>,
 }
@@ -35,12 +37,30 @@ pub async fn post_google(
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let payload: GoogleRequest = parse_json(body.as_ref())?;
+    sign_in(google::PROVIDER_ID, body, state).await
+}
+
+pub async fn post_oidc(
+    path: web::Path<String>,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    sign_in(path.as_str(), body, state).await
+}
+
+async fn sign_in(
+    provider: &str,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let payload: SignInRequest = parse_json(body.as_ref())?;
     require_policy(payload.policy_acceptance.as_ref())?;
-    let (user, token) = issue_session(&payload.id_token, &state).await?;
+    let (user, token, refresh_token) =
+        issue_session(provider, &payload.id_token, &state, true).await?;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "token": token,
+        "refresh_token": refresh_token,
         "user": user.profile(),
     })))
 }
@@ -49,9 +69,9 @@ pub async fn get_me(
     req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let user = require_user(&req, &state).await?;
+    let user = require_user(&req, &state, Scope::Read).await?;
     Ok(HttpResponse::Ok().json(serde_json::json!({
-        "user": user.profile(),
+        "user": user.account_profile(),
     })))
 }
 
@@ -72,32 +92,61 @@ pub async fn post_google_redirect(
         })?;
     let (target, acceptance) = parse_state(form.get("state"));
     require_policy(acceptance.as_ref())?;
-    let (_user, token) = issue_session(credential, &state).await?;
+    let (_user, token, _refresh_token) =
+        issue_session(google::PROVIDER_ID, credential, &state, false).await?;
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(web_views::redirect_html(&token, &target)))
 }
 
-async fn issue_session(
+pub async fn verify_identity(
+    provider: &str,
     id_token: &str,
     state: &AppState,
-) -> Result<(crate::domain::User, String), ApiError<serde_json::Value>> {
-    let claims = google::validate_id_token(id_token, &state.config.google_client_id)
+) -> Result<Identity, ApiError<serde_json::Value>> {
+    let provider = state
+        .providers
+        .get(provider)
+        .await
+        .map_err(|err| {
+            log::warn!("{err}");
+            ApiError::service_unavailable("provider_unavailable", "Identity provider unavailable")
+        })?
+        .ok_or_else(|| ApiError::not_found("provider_not_found", "Identity provider not found"))?;
+    oidc::validate_id_token(provider.as_ref(), state.providers.jwks(), id_token)
         .await
-        .map_err(|_| ApiError::unauthorized("token_invalid", "Token invalid"))?;
+        .map_err(|_| ApiError::unauthorized("token_invalid", "Token invalid"))
+}
+
+async fn issue_session(
+    provider: &str,
+    id_token: &str,
+    state: &AppState,
+    with_refresh: bool,
+) -> Result<(crate::domain::User, String, Option<String>), ApiError<serde_json::Value>> {
+    let identity = verify_identity(provider, id_token, state).await?;
     let user = state
         .storage
-        .find_or_create_user(&claims.sub, &claims.email)
+        .find_or_create_user(&identity.provider, &identity.subject, &identity.email)
         .await
         .map_err(|_| ApiError::internal())?;
+    let lifetime = sessions::SessionLifetime::from_config(&state.config);
     let token = sessions::generate_token();
-    let expires_at = sessions::expires_at(state.config.session_ttl_secs);
+    let family_id = state
+        .storage
+        .create_session(user.user_id, &token, lifetime.session_expires_at())
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if !with_refresh {
+        return Ok((user, token, None));
+    }
+    let refresh_token = sessions::generate_token();
     state
         .storage
-        .create_session(user.user_id, &token, expires_at)
+        .create_refresh_token(user.user_id, family_id, &refresh_token, lifetime.refresh_expires_at())
         .await
         .map_err(|_| ApiError::internal())?;
-    Ok((user, token))
+    Ok((user, token, Some(refresh_token)))
 }
 
 fn parse_form(body: &[u8]) -> HashMap<String, String> {
@@ -1,6 +1,8 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 
 use crate::api::helpers::{parse_limit_param, parse_query, parse_time_param, require_user};
+use crate::api::pagination::{note_cursor, notes_page, parse_cursor_param};
+use crate::auth::api_tokens::Scope;
 use crate::errors::ApiError;
 use crate::state::AppState;
 
@@ -8,16 +10,17 @@ pub async fn get_feed(
     req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let user = require_user(&req, &state).await?;
+    let user = require_user(&req, &state, Scope::Read).await?;
     let params = parse_query(&req);
     let from = parse_time_param(&params, "from")?;
     let to = parse_time_param(&params, "to")?;
+    let cursor = note_cursor(parse_cursor_param(&params, "cursor")?)?;
     let limit = parse_limit_param(&params, "limit", 50, 200)?;
 
     let notes = state
         .storage
-        .list_feed_notes(user.user_id, from, to, limit as i64)
+        .list_feed_notes(user.user_id, from, to, cursor, limit as i64 + 1)
         .await
         .map_err(|_| ApiError::internal())?;
-    Ok(HttpResponse::Ok().json(notes))
+    Ok(HttpResponse::Ok().json(notes_page(notes, limit)))
 }
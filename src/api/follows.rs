@@ -1,7 +1,11 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use serde::Deserialize;
 
-use crate::api::helpers::{parse_json, parse_query, parse_query_param, parse_uuid, require_user};
+use crate::api::helpers::{
+    parse_json, parse_limit_param, parse_query, parse_query_param, parse_uuid, require_user,
+};
+use crate::api::pagination::{edges_page, parse_cursor_param, user_cursor};
+use crate::auth::api_tokens::Scope;
 use crate::domain::note::format_timestamp;
 use crate::domain::Follow;
 use crate::errors::ApiError;
@@ -17,7 +21,7 @@ pub async fn post_follows(
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let follower = require_user(&req, &state).await?;
+    let follower = require_user(&req, &state, Scope::FollowsWrite).await?;
     let payload: FollowRequest = parse_json(body.as_ref())?;
     let followee_id = parse_uuid(&payload.followee_id, "invalid_user_id", "Invalid user id")?;
     if followee_id == follower.user_id {
@@ -53,7 +57,7 @@ pub async fn delete_follows(
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let follower = require_user(&req, &state).await?;
+    let follower = require_user(&req, &state, Scope::FollowsWrite).await?;
     let payload: FollowRequest = parse_json(body.as_ref())?;
     let followee_id = parse_uuid(&payload.followee_id, "invalid_user_id", "Invalid user id")?;
     if followee_id == follower.user_id {
@@ -89,16 +93,19 @@ pub async fn get_follows(
     })?;
 
     let user_id = parse_uuid(user_id, "invalid_user_id", "Invalid user id")?;
+    let cursor = user_cursor(parse_cursor_param(&params, "cursor")?)?;
+    let limit = parse_limit_param(&params, "limit", 100, 500)?;
+    let fetch_limit = limit as i64 + 1;
 
     let edges = match direction {
         "followers" => state
             .storage
-            .list_followers(user_id)
+            .list_followers(user_id, cursor, fetch_limit)
             .await
             .map_err(|_| ApiError::internal())?,
         "following" => state
             .storage
-            .list_following(user_id)
+            .list_following(user_id, cursor, fetch_limit)
             .await
             .map_err(|_| ApiError::internal())?,
         _ => {
@@ -110,5 +117,37 @@ pub async fn get_follows(
         }
     };
 
-    Ok(HttpResponse::Ok().json(serde_json::json!({"edges": edges})))
+    let page = edges_page(edges, limit);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "edges": page.items,
+        "next_cursor": page.next_cursor,
+    })))
+}
+
+/// Whether one user follows another, so clients need not page through a following list.
+pub async fn get_follow_check(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let params = parse_query(&req);
+    let follower = parse_query_param(&params, "follower").ok_or_else(|| {
+        ApiError::bad_request("missing_follower", "Missing follower parameter", None)
+    })?;
+    let followee = parse_query_param(&params, "followee").ok_or_else(|| {
+        ApiError::bad_request("missing_followee", "Missing followee parameter", None)
+    })?;
+    let follower_id = parse_uuid(follower, "invalid_user_id", "Invalid user id")?;
+    let followee_id = parse_uuid(followee, "invalid_user_id", "Invalid user id")?;
+
+    let created_at = state
+        .storage
+        .find_follow(follower_id, followee_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "follower_id": follower_id,
+        "followee_id": followee_id,
+        "following": created_at.is_some(),
+        "created_at": created_at.map(format_timestamp),
+    })))
 }
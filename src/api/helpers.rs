@@ -6,6 +6,8 @@ use time::OffsetDateTime;
 use url::form_urlencoded;
 use uuid::Uuid;
 
+use crate::auth::api_tokens::{is_api_token, Scope};
+use crate::auth::sessions::SessionLifetime;
 use crate::errors::ApiError;
 use crate::state::AppState;
 use crate::domain::NoteId;
@@ -116,21 +118,77 @@ pub fn parse_uuid(
         .map_err(|_| ApiError::bad_request(code, message, None))
 }
 
+/// Authenticates a session or a personal access token; sessions hold every scope.
 pub async fn require_user(
     req: &HttpRequest,
     state: &AppState,
+    scope: Scope,
 ) -> Result<crate::domain::User, ApiError<serde_json::Value>> {
     let token = bearer_token(req).ok_or_else(|| {
         ApiError::unauthorized("unauthorized", "Missing authorization token")
     })?;
+    if !is_api_token(&token) {
+        return require_session(req, state).await.map(|(user, _token)| user);
+    }
+    require_api_token(state, &token, scope).await
+}
+
+/// Feed readers cannot send headers, so feeds also take a personal access token as ?token=.
+pub async fn require_feed_user(
+    req: &HttpRequest,
+    state: &AppState,
+) -> Result<crate::domain::User, ApiError<serde_json::Value>> {
+    let params = parse_query(req);
+    match parse_query_param(&params, "token") {
+        Some(token) if is_api_token(token) => require_api_token(state, token, Scope::Read).await,
+        Some(_) => Err(ApiError::unauthorized("unauthorized", "Invalid api token")),
+        None => require_user(req, state, Scope::Read).await,
+    }
+}
+
+async fn require_api_token(
+    state: &AppState,
+    token: &str,
+    scope: Scope,
+) -> Result<crate::domain::User, ApiError<serde_json::Value>> {
+    let found = state
+        .storage
+        .get_api_token_user(token)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let (user, scopes) =
+        found.ok_or_else(|| ApiError::unauthorized("unauthorized", "Invalid api token"))?;
+    if !scopes.iter().any(|granted| granted == scope.as_str()) {
+        return Err(ApiError::forbidden(
+            "insufficient_scope",
+            "Token lacks the required scope",
+        ));
+    }
+    Ok(user)
+}
+
+pub async fn require_session(
+    req: &HttpRequest,
+    state: &AppState,
+) -> Result<(crate::domain::User, String), ApiError<serde_json::Value>> {
+    let token = bearer_token(req).ok_or_else(|| {
+        ApiError::unauthorized("unauthorized", "Missing authorization token")
+    })?;
+    if is_api_token(&token) {
+        return Err(ApiError::forbidden(
+            "insufficient_scope",
+            "Session management requires a session",
+        ));
+    }
 
     let user = state
         .storage
-        .get_session_user(&token)
+        .get_session_user(&token, SessionLifetime::from_config(&state.config))
         .await
         .map_err(|_| ApiError::internal())?;
 
-    user.ok_or_else(|| ApiError::unauthorized("unauthorized", "Invalid session"))
+    let user = user.ok_or_else(|| ApiError::unauthorized("unauthorized", "Invalid session"))?;
+    Ok((user, token))
 }
 
 fn bearer_token(req: &HttpRequest) -> Option<String> {
@@ -0,0 +1,41 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use crate::api::helpers::{parse_note_id, parse_query, parse_query_param};
+use crate::errors::ApiError;
+use crate::history::{fetch_history, find_newer_revision};
+use crate::related::fetch_chain;
+use crate::state::AppState;
+
+pub async fn get_note_history(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let note_id = parse_note_id(path.as_str())?;
+    let params = parse_query(&req);
+    let from = parse_query_param(&params, "from").map(parse_note_id).transpose()?;
+    let to = parse_query_param(&params, "to").map(parse_note_id).transpose()?;
+    let history = fetch_history(&state, note_id).await?;
+    let diff = history.diff(from, to)?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "post_id": history.post_id,
+        "revisions": history.revisions,
+        "diff": diff,
+    })))
+}
+
+/// Resolves any note of a post to the head of its newest version.
+pub async fn get_latest_version(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let note_id = parse_note_id(path.as_str())?;
+    let chain = fetch_chain(&state, note_id).await?;
+    let head = chain.head();
+    let newer = find_newer_revision(&state, head).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "post_id": head.id,
+        "superseded": newer.is_some(),
+        "latest": newer.as_ref().unwrap_or(head),
+    })))
+}
@@ -0,0 +1,81 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+use crate::api::auth::verify_identity;
+use crate::api::helpers::{parse_json, require_user};
+use crate::auth::api_tokens::Scope;
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::storage::{LinkOutcome, UnlinkOutcome};
+
+#[derive(Deserialize)]
+struct LinkRequest {
+    provider: String,
+    id_token: String,
+}
+
+pub async fn get_identities(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let identities = state
+        .storage
+        .list_identities(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"identities": identities})))
+}
+
+pub async fn post_identities(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let payload: LinkRequest = parse_json(body.as_ref())?;
+    let identity = verify_identity(&payload.provider, &payload.id_token, &state).await?;
+    let outcome = state
+        .storage
+        .link_identity(user.user_id, &identity.provider, &identity.subject, &identity.email)
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    match outcome {
+        LinkOutcome::Linked(identity) => Ok(HttpResponse::Created().json(identity)),
+        LinkOutcome::ProviderLinked => Err(ApiError::conflict(
+            "identity_exists",
+            "Provider already linked to this account",
+        )),
+        LinkOutcome::TakenByOtherUser => Err(ApiError::conflict(
+            "identity_taken",
+            "Identity is linked to another account",
+        )),
+    }
+}
+
+pub async fn delete_identity(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let outcome = state
+        .storage
+        .unlink_identity(user.user_id, path.as_str())
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    match outcome {
+        UnlinkOutcome::Unlinked => Ok(HttpResponse::Ok().json(serde_json::json!({"status": "unlinked"}))),
+        UnlinkOutcome::NotFound => Err(ApiError::not_found(
+            "identity_not_found",
+            "Identity not linked",
+        )),
+        UnlinkOutcome::LastIdentity => Err(ApiError::unprocessable(
+            "last_identity",
+            "Cannot unlink the only sign-in identity",
+            None,
+        )),
+    }
+}
@@ -1,10 +1,21 @@
+pub mod activitypub;
 pub mod associations;
 pub mod account;
+pub mod api_tokens;
 pub mod auth;
 pub mod feed;
 pub mod follows;
 pub mod health;
+pub mod history;
 pub mod helpers;
+pub mod identities;
 pub mod notes;
+pub mod notifications;
+pub mod pagination;
 pub mod related;
+pub mod retractions;
+pub mod search;
+pub mod sessions;
+pub mod stream;
 pub mod users;
+pub mod webhooks;
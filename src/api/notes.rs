@@ -1,20 +1,37 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use serde::{Deserialize, Serialize};
 
+use crate::api::activitypub::{queue_post_delivery, Delivery};
+use crate::api::associations::ensure_association_target;
 use crate::api::helpers::{
-    parse_json, parse_limit_param, parse_note_id, parse_query, parse_query_param,
-    parse_time_param, parse_uuid, require_user,
+    parse_json, parse_limit_param, parse_note_id, parse_note_reference, parse_query,
+    parse_query_param, parse_time_param, parse_uuid, require_user,
 };
-use crate::domain::Note;
+use crate::api::pagination::{note_cursor, notes_page, parse_cursor_param};
+use crate::auth::api_tokens::Scope;
+use crate::domain::{Note, NoteId, User};
 use crate::errors::ApiError;
 use crate::related::fetch_chain;
 use crate::state::AppState;
+use crate::storage::AssociationInsertError;
+use crate::urls::base32::encode_id;
+
+const MAX_POST_LINKS: usize = 20;
 
 #[derive(Deserialize)]
 struct CreateNote {
     value: String,
 }
 
+#[derive(Deserialize)]
+struct CreatePost {
+    value: String,
+    reply_to: Option<String>,
+    quote_of: Option<String>,
+    #[serde(default)]
+    links: Vec<String>,
+}
+
 #[derive(Serialize)]
 struct PostResponse {
     root: Note,
@@ -26,16 +43,33 @@ pub async fn post_notes(
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let user = require_user(&req, &state).await?;
-    let payload: CreateNote = parse_json(body.as_ref())?;
+    let user = require_user(&req, &state, Scope::NotesWrite).await?;
+    let payload: CreatePost = parse_json(body.as_ref())?;
+    if payload.reply_to.is_some() || payload.quote_of.is_some() || !payload.links.is_empty() {
+        // Same scope POST /associations asks for; sessions carry every scope.
+        require_user(&req, &state, Scope::AssociationsWrite).await?;
+    }
+    let links = resolve_post_links(&state, &user, &payload).await?;
     let segments = split_note_value(&payload.value, 1024);
     let bytes: Vec<Vec<u8>> = segments.iter().map(|value| value.as_bytes().to_vec()).collect();
-    let account_note_id = account_note_id(&user)?;
     let (root, segments) = state
         .storage
-        .create_note_chain(&bytes, user.user_id, account_note_id)
+        .create_note_chain(&bytes, user.user_id, &links)
         .await
-        .map_err(|_| ApiError::internal())?;
+        .map_err(|err| {
+            // The target was retracted after the checks below but before the post committed.
+            if let Some(AssociationInsertError::TargetUnavailable) =
+                err.downcast_ref::<AssociationInsertError>()
+            {
+                return ApiError::unprocessable(
+                    "note_retracted",
+                    "Retracted notes cannot be associated",
+                    None,
+                );
+            }
+            ApiError::internal()
+        })?;
+    queue_post_delivery(&state, user.user_id, parse_note_id(&root.id)?, Delivery::Create).await;
 
     Ok(HttpResponse::Created().json(PostResponse { root, segments }))
 }
@@ -46,7 +80,7 @@ pub async fn post_note_version(
     body: web::Bytes,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
-    let user = require_user(&req, &state).await?;
+    let user = require_user(&req, &state, Scope::NotesWrite).await?;
     let source_id = parse_note_id(path.as_str())?;
     let payload: CreateNote = parse_json(body.as_ref())?;
 
@@ -59,6 +93,9 @@ pub async fn post_note_version(
             "Cannot edit this note",
         ));
     }
+    if head_note.is_retracted() {
+        return Err(ApiError::conflict("note_retracted", "Retracted notes cannot be edited"));
+    }
 
     let head_id = chain
         .prev
@@ -67,26 +104,16 @@ pub async fn post_note_version(
         .unwrap_or(&chain.center.id);
     let head_id = parse_note_id(head_id).map_err(|_| ApiError::internal())?;
 
-    let locked = state
-        .storage
-        .is_account_note_id(head_id)
-        .await
-        .map_err(|_| ApiError::internal())?;
-    if locked {
-        return Err(ApiError::unprocessable(
-            "account_note_locked",
-            "Account bootstrap notes cannot be versioned",
-            None,
-        ));
+    if account_note_id(&user).ok() == Some(head_id) {
+        return post_account_note_version(&state, &user, &payload.value).await;
     }
 
     let segments = split_note_value(&payload.value, 1024);
     let bytes: Vec<Vec<u8>> = segments.iter().map(|value| value.as_bytes().to_vec()).collect();
-    let account_note_id = account_note_id(&user)?;
 
     let (root, segments) = state
         .storage
-        .create_note_version_chain(head_id, &bytes, user.user_id, account_note_id)
+        .create_note_version_chain(head_id, &bytes, user.user_id)
         .await
         .map_err(|err| {
             if let Some(crate::storage::AssociationInsertError::VersionExists) =
@@ -103,6 +130,76 @@ pub async fn post_note_version(
     Ok(HttpResponse::Created().json(PostResponse { root, segments }))
 }
 
+/// The associations a new post starts with, as (kind, target) pairs, checked the same way
+/// POST /associations checks them. Targets accept note ids or note URLs.
+async fn resolve_post_links(
+    state: &AppState,
+    user: &User,
+    payload: &CreatePost,
+) -> Result<Vec<(&'static str, NoteId)>, ApiError<serde_json::Value>> {
+    if payload.links.len() > MAX_POST_LINKS {
+        return Err(ApiError::unprocessable(
+            "too_many_links",
+            "At most 20 links per post",
+            None,
+        ));
+    }
+    let mut requested: Vec<(&'static str, NoteId)> = Vec::new();
+    let references = payload
+        .reply_to
+        .iter()
+        .map(|value| ("reply", value))
+        .chain(payload.quote_of.iter().map(|value| ("quote", value)))
+        .chain(payload.links.iter().map(|value| ("link", value)));
+    for (kind, value) in references {
+        let target = parse_note_reference(value)?;
+        if !requested.contains(&(kind, target)) {
+            requested.push((kind, target));
+        }
+    }
+    if requested.is_empty() {
+        return Ok(requested);
+    }
+
+    let ids: Vec<NoteId> = requested.iter().map(|(_, id)| *id).collect();
+    let notes = state
+        .storage
+        .find_notes_by_ids(&ids)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    for (kind, id) in &requested {
+        let encoded = encode_id(id.to_bytes());
+        let note = notes
+            .iter()
+            .find(|note| note.id == encoded)
+            .ok_or_else(|| ApiError::not_found("note_not_found", "Note not found"))?;
+        ensure_association_target(kind, user.user_id, note, user)?;
+    }
+    Ok(requested)
+}
+
+/// Account notes stay a single segment; a new version also becomes the user's account note.
+async fn post_account_note_version(
+    state: &AppState,
+    user: &crate::domain::User,
+    value: &str,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    if value.len() > 1024 {
+        return Err(ApiError::unprocessable(
+            "value_too_large",
+            "Account note exceeds 1024 bytes",
+            None,
+        ));
+    }
+    let root = state
+        .storage
+        .replace_account_note(user.user_id, value.as_bytes())
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let segments = vec![root.id.clone()];
+    Ok(HttpResponse::Created().json(PostResponse { root, segments }))
+}
+
 pub async fn get_note_by_id(
     path: web::Path<String>,
     state: web::Data<AppState>,
@@ -130,13 +227,15 @@ pub async fn get_notes(
 
     let from = parse_time_param(&params, "from")?;
     let to = parse_time_param(&params, "to")?;
+    let cursor = note_cursor(parse_cursor_param(&params, "cursor")?)?;
+    let limit = parse_limit_param(&params, "limit", 50, 200)?;
 
     let notes = state
         .storage
-        .list_notes(author, from, to)
+        .list_notes(author, from, to, cursor, limit as i64 + 1)
         .await
         .map_err(|_| ApiError::internal())?;
-    Ok(HttpResponse::Ok().json(notes))
+    Ok(HttpResponse::Ok().json(notes_page(notes, limit)))
 }
 
 pub async fn get_random_notes(
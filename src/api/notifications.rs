@@ -0,0 +1,103 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::helpers::{
+    parse_json, parse_limit_param, parse_query, parse_query_param, parse_uuid, require_user,
+};
+use crate::api::pagination::{notifications_page, parse_cursor_param, user_cursor};
+use crate::auth::api_tokens::Scope;
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+const MAX_READ_IDS: usize = 200;
+
+#[derive(Deserialize, Default)]
+struct MarkReadRequest {
+    ids: Option<Vec<String>>,
+}
+
+pub async fn get_notifications(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Read).await?;
+    let params = parse_query(&req);
+    let unread_only = match parse_query_param(&params, "unread") {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(_) => {
+            return Err(ApiError::bad_request(
+                "invalid_unread",
+                "unread must be true or false",
+                None,
+            ))
+        }
+    };
+    let cursor = user_cursor(parse_cursor_param(&params, "cursor")?)?;
+    let limit = parse_limit_param(&params, "limit", 20, 100)?;
+
+    let notifications = state
+        .storage
+        .list_notifications(user.user_id, unread_only, cursor, limit as i64 + 1)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let unread_count = state
+        .storage
+        .count_unread_notifications(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let page = notifications_page(notifications, limit);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "items": page.items,
+        "next_cursor": page.next_cursor,
+        "unread_count": unread_count,
+    })))
+}
+
+/// Marks the listed notifications read, or every unread one when the body has no ids.
+pub async fn post_notifications_read(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::NotificationsWrite).await?;
+    let payload: MarkReadRequest = if body.is_empty() {
+        MarkReadRequest::default()
+    } else {
+        parse_json(body.as_ref())?
+    };
+
+    let ids = match payload.ids {
+        Some(values) => {
+            if values.len() > MAX_READ_IDS {
+                return Err(ApiError::unprocessable(
+                    "too_many_ids",
+                    "At most 200 ids per request",
+                    None,
+                ));
+            }
+            let ids = values
+                .iter()
+                .map(|value| parse_uuid(value, "invalid_notification_id", "Invalid notification id"))
+                .collect::<Result<Vec<Uuid>, _>>()?;
+            Some(ids)
+        }
+        None => None,
+    };
+
+    let updated = state
+        .storage
+        .mark_notifications_read(user.user_id, ids.as_deref())
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let unread_count = state
+        .storage
+        .count_unread_notifications(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "updated": updated,
+        "unread_count": unread_count,
+    })))
+}
@@ -0,0 +1,69 @@
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::api::helpers::parse_query_param;
+use crate::domain::{Cursor, FollowEdge, Note, NoteId, Notification, Page, WebhookDelivery};
+use crate::errors::ApiError;
+use crate::urls::base32::decode_id;
+
+pub fn parse_cursor_param(
+    params: &[(String, String)],
+    key: &str,
+) -> Result<Option<Cursor>, ApiError<serde_json::Value>> {
+    match parse_query_param(params, key) {
+        Some(value) => Cursor::decode(value)
+            .map(Some)
+            .ok_or_else(invalid_cursor),
+        None => Ok(None),
+    }
+}
+
+pub fn note_cursor(
+    cursor: Option<Cursor>,
+) -> Result<Option<(OffsetDateTime, NoteId)>, ApiError<serde_json::Value>> {
+    let Some(cursor) = cursor else { return Ok(None) };
+    let bytes = decode_id(&cursor.key).ok_or_else(invalid_cursor)?;
+    Ok(Some((cursor.created_at, NoteId::from_bytes(bytes))))
+}
+
+pub fn user_cursor(
+    cursor: Option<Cursor>,
+) -> Result<Option<(OffsetDateTime, Uuid)>, ApiError<serde_json::Value>> {
+    let Some(cursor) = cursor else { return Ok(None) };
+    let user_id = cursor.key.parse::<Uuid>().map_err(|_| invalid_cursor())?;
+    Ok(Some((cursor.created_at, user_id)))
+}
+
+pub fn notes_page(rows: Vec<Note>, limit: usize) -> Page<Note> {
+    Page::from_rows(rows, limit, |note| {
+        timestamp_cursor(&note.created_at, note.id.clone())
+    })
+}
+
+pub fn edges_page(rows: Vec<FollowEdge>, limit: usize) -> Page<FollowEdge> {
+    Page::from_rows(rows, limit, |edge| {
+        timestamp_cursor(&edge.created_at, edge.user.user_id.to_string())
+    })
+}
+
+pub fn notifications_page(rows: Vec<Notification>, limit: usize) -> Page<Notification> {
+    Page::from_rows(rows, limit, |notification| {
+        timestamp_cursor(&notification.created_at, notification.id.to_string())
+    })
+}
+
+pub fn deliveries_page(rows: Vec<WebhookDelivery>, limit: usize) -> Page<WebhookDelivery> {
+    Page::from_rows(rows, limit, |delivery| {
+        timestamp_cursor(&delivery.created_at, delivery.id.to_string())
+    })
+}
+
+fn timestamp_cursor(created_at: &str, key: String) -> Option<Cursor> {
+    let created_at = OffsetDateTime::parse(created_at, &Rfc3339).ok()?;
+    Some(Cursor { created_at, key })
+}
+
+fn invalid_cursor() -> ApiError<serde_json::Value> {
+    ApiError::bad_request("invalid_cursor", "Invalid cursor", None)
+}
@@ -1,8 +1,8 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-use crate::api::helpers::parse_note_id;
+use crate::api::helpers::{parse_limit_param, parse_note_id, parse_query};
 use crate::errors::ApiError;
-use crate::related::fetch_related;
+use crate::related::{fetch_related, fetch_thread};
 use crate::state::AppState;
 
 pub async fn get_related(
@@ -13,3 +13,16 @@ pub async fn get_related(
     let response = fetch_related(&state, note_id).await?;
     Ok(HttpResponse::Ok().json(response))
 }
+
+pub async fn get_thread(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let note_id = parse_note_id(path.as_str())?;
+    let params = parse_query(&req);
+    let depth = parse_limit_param(&params, "depth", 4, 8)?;
+    let breadth = parse_limit_param(&params, "limit", 10, 50)?;
+    let thread = fetch_thread(&state, note_id, depth, breadth).await?;
+    Ok(HttpResponse::Ok().json(thread))
+}
@@ -0,0 +1,59 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use crate::api::helpers::{parse_note_id, require_user};
+use crate::auth::api_tokens::Scope;
+use crate::errors::ApiError;
+use crate::related::fetch_chain;
+use crate::state::AppState;
+
+pub async fn delete_note(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::NotesWrite).await?;
+    let note_id = parse_note_id(path.as_str())?;
+    let chain = fetch_chain(&state, note_id).await?;
+
+    let head_note = chain.prev.first().unwrap_or(&chain.center);
+    if head_note.author.user_id != user.user_id {
+        return Err(ApiError::forbidden(
+            "retract_forbidden",
+            "Cannot retract this note",
+        ));
+    }
+    if user.account_note_id.as_deref() == Some(head_note.id.as_str()) {
+        return Err(ApiError::unprocessable(
+            "account_note_locked",
+            "Account notes cannot be retracted",
+            None,
+        ));
+    }
+    if head_note.is_retracted() {
+        return Err(ApiError::conflict("note_retracted", "Note already retracted"));
+    }
+
+    let segments: Vec<String> = chain
+        .prev
+        .iter()
+        .chain(std::iter::once(&chain.center))
+        .chain(chain.next.iter())
+        .map(|note| note.id.clone())
+        .collect();
+    let ids = segments
+        .iter()
+        .map(|id| parse_note_id(id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::internal())?;
+    state
+        .storage
+        .retract_notes(&ids)
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "retracted",
+        "root": head_note.id,
+        "segments": segments,
+    })))
+}
@@ -0,0 +1,44 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use crate::api::helpers::{
+    parse_limit_param, parse_query, parse_query_param, parse_time_param, parse_uuid,
+};
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+const MAX_QUERY_CHARS: usize = 256;
+
+pub async fn get_search(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let params = parse_query(&req);
+    let query = parse_query_param(&params, "q").map(str::trim).unwrap_or("");
+    if query.is_empty() {
+        return Err(ApiError::bad_request("missing_query", "Missing q parameter", None));
+    }
+    if query.chars().count() > MAX_QUERY_CHARS {
+        return Err(ApiError::unprocessable(
+            "query_too_long",
+            "Search query exceeds 256 characters",
+            None,
+        ));
+    }
+    let author = match parse_query_param(&params, "author") {
+        Some(value) => Some(parse_uuid(value, "invalid_author", "Invalid author id")?),
+        None => None,
+    };
+    let from = parse_time_param(&params, "from")?;
+    let to = parse_time_param(&params, "to")?;
+    let limit = parse_limit_param(&params, "limit", 20, 100)?;
+
+    let notes = state
+        .storage
+        .search_notes(query, author, from, to, limit as i64)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "query": query,
+        "items": notes,
+    })))
+}
@@ -0,0 +1,108 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+use crate::api::helpers::{parse_json, parse_uuid, require_session};
+use crate::auth::sessions::{generate_token, SessionLifetime};
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::storage::RefreshOutcome;
+
+#[derive(Deserialize)]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+pub async fn post_refresh(
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let payload: RefreshRequest = parse_json(body.as_ref())?;
+    let token = generate_token();
+    let refresh_token = generate_token();
+    let outcome = state
+        .storage
+        .rotate_refresh_token(
+            &payload.refresh_token,
+            &token,
+            &refresh_token,
+            SessionLifetime::from_config(&state.config),
+        )
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    match outcome {
+        RefreshOutcome::Rotated(user) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "token": token,
+            "refresh_token": refresh_token,
+            "user": user.profile(),
+        }))),
+        RefreshOutcome::Reused => Err(ApiError::unauthorized(
+            "refresh_reused",
+            "Refresh token already used; session revoked",
+        )),
+        RefreshOutcome::Invalid => Err(ApiError::unauthorized(
+            "refresh_invalid",
+            "Invalid refresh token",
+        )),
+    }
+}
+
+pub async fn post_logout(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let (_user, token) = require_session(&req, &state).await?;
+    state
+        .storage
+        .delete_session_by_token(&token)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"status": "signed_out"})))
+}
+
+pub async fn get_sessions(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let (user, token) = require_session(&req, &state).await?;
+    let sessions = state
+        .storage
+        .list_sessions(user.user_id, &token)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"sessions": sessions})))
+}
+
+pub async fn delete_session(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let (user, _token) = require_session(&req, &state).await?;
+    let session_id = parse_uuid(path.as_str(), "invalid_session_id", "Invalid session id")?;
+    let deleted = state
+        .storage
+        .delete_session(user.user_id, session_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if !deleted {
+        return Err(ApiError::not_found("session_not_found", "Session not found"));
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"status": "revoked"})))
+}
+
+pub async fn delete_other_sessions(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let (user, token) = require_session(&req, &state).await?;
+    let revoked = state
+        .storage
+        .delete_other_sessions(user.user_id, &token)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "revoked",
+        "revoked": revoked,
+    })))
+}
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::web::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Serialize;
+use tokio::sync::{broadcast, mpsc};
+use uuid::Uuid;
+
+use crate::api::helpers::{parse_note_id, require_feed_user};
+use crate::domain::{Association, Note, NoteId, StreamEvent};
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::storage::Storage;
+use crate::urls::base32::decode_id;
+
+const HEARTBEAT_SECS: u64 = 15;
+const FRAME_BUFFER: usize = 32;
+const RETRY_FRAME: &[u8] = b"retry: 3000\n\n";
+const HEARTBEAT_FRAME: &[u8] = b": keep-alive\n\n";
+
+/// Response body fed by the task that filters events for one connection. Dropping it (the
+/// client went away) closes the channel, which stops that task.
+struct EventStream {
+    frames: mpsc::Receiver<Bytes>,
+}
+
+impl MessageBody for EventStream {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.frames.poll_recv(cx).map(|frame| frame.map(Ok))
+    }
+}
+
+#[derive(Serialize)]
+struct NoteFrame<'a> {
+    note: &'a Note,
+    supersedes: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct AssociationFrame<'a> {
+    association: &'a Association,
+    note: &'a Note,
+}
+
+/// New posts by the user and the accounts they follow, as `note` events.
+pub async fn get_feed_stream(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_feed_user(&req, &state).await?;
+    // Subscribe before loading follows so a follow made in between is not missed.
+    let events = state.realtime.subscribe();
+    let followees = state
+        .storage
+        .list_followee_ids(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let mut followees: HashSet<Uuid> = followees.into_iter().collect();
+    followees.insert(user.user_id);
+    let user_id = user.user_id;
+    Ok(spawn_stream(&state, events, move |storage, event| {
+        let frame = match event {
+            StreamEvent::Follow {
+                follower_id,
+                followee_id,
+                following,
+            } if follower_id == user_id => {
+                if following {
+                    followees.insert(followee_id);
+                } else if followee_id != user_id {
+                    followees.remove(&followee_id);
+                }
+                None
+            }
+            StreamEvent::Post {
+                note_id,
+                author_id,
+                supersedes,
+            } if followees.contains(&author_id) => Some((note_id, supersedes)),
+            _ => None,
+        };
+        async move {
+            let (note_id, supersedes) = frame?;
+            let note = load_note(&storage, &note_id).await?;
+            event_frame(
+                "note",
+                &NoteFrame {
+                    note: &note,
+                    supersedes: supersedes.as_deref(),
+                },
+            )
+        }
+    }))
+}
+
+/// Replies and links to a post and newer versions of it, as `association` events carrying the
+/// other note. Any note of a chained post subscribes to the whole post.
+pub async fn get_note_stream(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let note_id = parse_note_id(path.as_str())?;
+    let events = state.realtime.subscribe();
+    let (center, prev, next) = state
+        .storage
+        .find_note_chain(note_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("note_not_found", "Note not found"))?;
+    let post_ids: HashSet<String> = std::iter::once(center)
+        .chain(prev)
+        .chain(next)
+        .map(|note| note.id)
+        .collect();
+    Ok(spawn_stream(&state, events, move |storage, event| {
+        let matched = match event {
+            StreamEvent::Association(association) => match association.kind.as_str() {
+                "reply" | "link" if post_ids.contains(&association.to_id) => {
+                    Some((association.from_id.clone(), association))
+                }
+                "version" if post_ids.contains(&association.from_id) => {
+                    Some((association.to_id.clone(), association))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        async move {
+            let (other_id, association) = matched?;
+            let note = load_note(&storage, &other_id).await?;
+            event_frame(
+                "association",
+                &AssociationFrame {
+                    association: &association,
+                    note: &note,
+                },
+            )
+        }
+    }))
+}
+
+/// Starts the per-connection task and returns the streaming response. `filter` maps each event
+/// to the frame to send, if any; it runs in order, so state it keeps (like follows) stays current.
+fn spawn_stream<F, Fut>(
+    state: &AppState,
+    mut events: broadcast::Receiver<StreamEvent>,
+    mut filter: F,
+) -> HttpResponse
+where
+    F: FnMut(Storage, StreamEvent) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Option<Bytes>> + Send,
+{
+    let (frames, receiver) = mpsc::channel(FRAME_BUFFER);
+    let storage = state.storage.clone();
+    tokio::spawn(async move {
+        if frames.send(Bytes::from_static(RETRY_FRAME)).await.is_err() {
+            return;
+        }
+        let mut heartbeat = tokio::time::interval(Duration::from_secs(HEARTBEAT_SECS));
+        heartbeat.tick().await;
+        loop {
+            let frame = tokio::select! {
+                received = events.recv() => match received {
+                    Ok(event) => filter(storage.clone(), event).await,
+                    // A subscriber that fell behind has missed events; closing makes the client
+                    // reconnect and reload instead of silently showing a gap.
+                    Err(broadcast::error::RecvError::Lagged(_)) | Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = heartbeat.tick() => Some(Bytes::from_static(HEARTBEAT_FRAME)),
+                _ = frames.closed() => break,
+            };
+            if let Some(frame) = frame {
+                if frames.send(frame).await.is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        // Stop nginx-style proxies from buffering the stream.
+        .insert_header(("X-Accel-Buffering", "no"))
+        .body(EventStream { frames: receiver })
+}
+
+async fn load_note(storage: &Storage, note_id: &str) -> Option<Note> {
+    let note_id = NoteId::from_bytes(decode_id(note_id)?);
+    match storage.find_note(note_id).await {
+        Ok(note) => note.filter(|note| note.retracted_at.is_none()),
+        Err(err) => {
+            log::error!("stream note lookup failed: {err}");
+            None
+        }
+    }
+}
+
+fn event_frame(event: &str, data: &impl Serialize) -> Option<Bytes> {
+    let json = serde_json::to_string(data).ok()?;
+    Some(Bytes::from(format!("event: {event}\ndata: {json}\n\n")))
+}
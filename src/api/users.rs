@@ -27,3 +27,23 @@ pub async fn get_user_by_id(
 
     Ok(HttpResponse::Ok().json(serde_json::json!({"user": user.profile()})))
 }
+
+pub async fn get_account_note_history(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user_id = parse_uuid(path.as_str(), "invalid_user_id", "Invalid user id")?;
+    let user = state
+        .storage
+        .find_user_by_id(user_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+
+    let notes = state
+        .storage
+        .list_account_note_history(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"notes": notes})))
+}
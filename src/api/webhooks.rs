@@ -0,0 +1,149 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use url::Url;
+
+use crate::api::helpers::{parse_json, parse_limit_param, parse_query, parse_uuid, require_user};
+use crate::api::pagination::{deliveries_page, parse_cursor_param, user_cursor};
+use crate::auth::api_tokens::Scope;
+use crate::auth::sessions::generate_token;
+use crate::domain::WEBHOOK_EVENTS;
+use crate::errors::ApiError;
+use crate::outbound;
+use crate::state::AppState;
+
+const MAX_WEBHOOKS: usize = 10;
+const MAX_URL_CHARS: usize = 2048;
+
+#[derive(Deserialize)]
+struct CreateWebhookRequest {
+    url: String,
+    events: Vec<String>,
+}
+
+pub async fn get_webhooks(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let webhooks = state
+        .storage
+        .list_webhooks(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"webhooks": webhooks})))
+}
+
+pub async fn post_webhooks(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let payload: CreateWebhookRequest = parse_json(body.as_ref())?;
+
+    let url = payload.url.trim();
+    let parsed = Url::parse(url)
+        .ok()
+        .filter(|_| url.len() <= MAX_URL_CHARS)
+        .filter(|parsed| parsed.host_str().is_some())
+        .ok_or_else(|| ApiError::unprocessable("invalid_url", "Webhook URL must be an absolute URL", None))?;
+    match parsed.scheme() {
+        "https" => {}
+        "http" if state.config.webhook_allow_insecure => {}
+        _ => {
+            return Err(ApiError::unprocessable(
+                "invalid_url",
+                "Webhook URL must use https",
+                None,
+            ))
+        }
+    }
+    if !state.config.webhook_allow_insecure {
+        outbound::check_public_host(&parsed).await.map_err(|err| {
+            log::info!("webhook URL refused: {err}");
+            ApiError::unprocessable("invalid_url", "Webhook URL must point to a public address", None)
+        })?;
+    }
+
+    let mut events: Vec<String> = Vec::new();
+    for value in &payload.events {
+        if !WEBHOOK_EVENTS.contains(&value.as_str()) {
+            return Err(ApiError::unprocessable(
+                "invalid_event",
+                "Unknown event",
+                Some(serde_json::json!({"event": value, "allowed": WEBHOOK_EVENTS})),
+            ));
+        }
+        if !events.contains(value) {
+            events.push(value.clone());
+        }
+    }
+    if events.is_empty() {
+        return Err(ApiError::unprocessable(
+            "invalid_event",
+            "At least one event is required",
+            None,
+        ));
+    }
+
+    let existing = state
+        .storage
+        .list_webhooks(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if existing.len() >= MAX_WEBHOOKS {
+        return Err(ApiError::conflict("webhook_limit", "Webhook limit reached"));
+    }
+
+    let secret = format!("whsec_{}", generate_token());
+    let created = state
+        .storage
+        .create_webhook(user.user_id, parsed.as_str(), &secret, &events)
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    let mut response = serde_json::to_value(created).map_err(|_| ApiError::internal())?;
+    response["secret"] = serde_json::Value::String(secret);
+    Ok(HttpResponse::Created().json(response))
+}
+
+pub async fn delete_webhook(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let webhook_id = parse_uuid(path.as_str(), "invalid_webhook_id", "Invalid webhook id")?;
+    let deleted = state
+        .storage
+        .delete_webhook(user.user_id, webhook_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    if !deleted {
+        return Err(ApiError::not_found("webhook_not_found", "Webhook not found"));
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"status": "deleted"})))
+}
+
+pub async fn get_webhook_deliveries(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_user(&req, &state, Scope::Account).await?;
+    let webhook_id = parse_uuid(path.as_str(), "invalid_webhook_id", "Invalid webhook id")?;
+    let params = parse_query(&req);
+    let cursor = user_cursor(parse_cursor_param(&params, "cursor")?)?;
+    let limit = parse_limit_param(&params, "limit", 50, 200)?;
+    let deliveries = state
+        .storage
+        .list_webhook_deliveries(user.user_id, webhook_id, cursor, limit as i64 + 1)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("webhook_not_found", "Webhook not found"))?;
+    let page = deliveries_page(deliveries, limit);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "deliveries": page.items,
+        "next_cursor": page.next_cursor,
+    })))
+}
@@ -0,0 +1,48 @@
+use crate::auth::sessions::generate_token;
+
+/// Personal access tokens carry this prefix so they never collide with session tokens.
+pub const PREFIX: &str = "lgxpat_";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Read,
+    NotesWrite,
+    AssociationsWrite,
+    FollowsWrite,
+    NotificationsWrite,
+    /// Account, session and token management; only sessions carry it.
+    Account,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::NotesWrite => "notes:write",
+            Scope::AssociationsWrite => "associations:write",
+            Scope::FollowsWrite => "follows:write",
+            Scope::NotificationsWrite => "notifications:write",
+            Scope::Account => "account",
+        }
+    }
+
+    /// Parses a scope that may be granted to a personal access token.
+    pub fn parse_grantable(value: &str) -> Option<Self> {
+        match value {
+            "read" => Some(Scope::Read),
+            "notes:write" => Some(Scope::NotesWrite),
+            "associations:write" => Some(Scope::AssociationsWrite),
+            "follows:write" => Some(Scope::FollowsWrite),
+            "notifications:write" => Some(Scope::NotificationsWrite),
+            _ => None,
+        }
+    }
+}
+
+pub fn generate_api_token() -> String {
+    format!("{PREFIX}{}", generate_token())
+}
+
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(PREFIX)
+}
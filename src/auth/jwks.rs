@@ -0,0 +1,249 @@
+use reqwest::header::CACHE_CONTROL;
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Jwk {
+    pub kid: String,
+    pub kty: String,
+    pub n: String,
+    pub e: String,
+}
+
+#[derive(Default)]
+struct JwksState {
+    keys: HashMap<String, Jwk>,
+    expires_at: Option<Instant>,
+    fetched_at: Option<Instant>,
+}
+
+/// Signing keys per JWKS URI.
+///
+/// Each URI has its own state, locked only to read or store keys, never across a fetch. The miss
+/// that claims a refresh fetches; unknown `kid`s refetch at most once per `min_refresh`, and other
+/// misses meanwhile use the cached keys. `file://` URIs load a static key set once, for offline setups.
+pub struct JwksCache {
+    client: Client,
+    min_refresh: Duration,
+    default_ttl: Duration,
+    entries: RwLock<HashMap<String, Arc<Mutex<JwksState>>>>,
+}
+
+impl JwksCache {
+    pub fn new(min_refresh_secs: u64, default_ttl_secs: u64) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .redirect(Policy::none())
+                .build()
+                .unwrap_or_default(),
+            min_refresh: Duration::from_secs(min_refresh_secs),
+            default_ttl: Duration::from_secs(default_ttl_secs),
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_key(&self, jwks_uri: &str, kid: &str) -> Result<Jwk, String> {
+        let entry = self.entry(jwks_uri).await;
+        let ticket = {
+            let mut state = entry.lock().await;
+            let now = Instant::now();
+            // expires_at stays None for static key sets, which never expire.
+            let fresh = state.fetched_at.is_some() && state.expires_at.is_none_or(|at| at > now);
+            if fresh {
+                if let Some(jwk) = state.keys.get(kid) {
+                    return Ok(jwk.clone());
+                }
+            }
+            let throttled = state
+                .fetched_at
+                .is_some_and(|at| now.duration_since(at) < self.min_refresh);
+            if throttled {
+                return lookup(&state, kid);
+            }
+            // Claiming the refresh makes other misses see it as throttled while this one fetches.
+            state.fetched_at = Some(now);
+            now
+        };
+        let fetched = self.fetch(jwks_uri).await;
+        let mut state = entry.lock().await;
+        match fetched {
+            // A refresh claimed after this one has the newer keys.
+            Ok((keys, ttl)) if state.fetched_at == Some(ticket) => {
+                state.keys = keys;
+                state.expires_at = ticket.checked_add(ttl.max(self.min_refresh));
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("jwks refresh failed for {jwks_uri}: {err}"),
+        }
+        lookup(&state, kid)
+    }
+
+    async fn entry(&self, jwks_uri: &str) -> Arc<Mutex<JwksState>> {
+        if let Some(entry) = self.entries.read().await.get(jwks_uri) {
+            return entry.clone();
+        }
+        self.entries
+            .write()
+            .await
+            .entry(jwks_uri.to_string())
+            .or_default()
+            .clone()
+    }
+
+    async fn fetch(&self, jwks_uri: &str) -> Result<(HashMap<String, Jwk>, Duration), String> {
+        let (set, ttl) = if let Some(path) = jwks_uri.strip_prefix("file://") {
+            let raw = tokio::fs::read(path).await.map_err(|err| err.to_string())?;
+            let set: JwkSet = serde_json::from_slice(&raw).map_err(|err| err.to_string())?;
+            (set, Duration::MAX)
+        } else {
+            let res = self
+                .client
+                .get(jwks_uri)
+                .send()
+                .await
+                .and_then(|res| res.error_for_status())
+                .map_err(|err| err.to_string())?;
+            let ttl = res
+                .headers()
+                .get(CACHE_CONTROL)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_max_age)
+                .unwrap_or(self.default_ttl);
+            let set: JwkSet = res.json().await.map_err(|err| err.to_string())?;
+            (set, ttl)
+        };
+        let keys = set
+            .keys
+            .into_iter()
+            .filter(|k| k.kty == "RSA")
+            .map(|jwk| (jwk.kid.clone(), jwk))
+            .collect();
+        Ok((keys, ttl))
+    }
+}
+
+/// Stale keys keep working while the issuer is unreachable or refreshes are throttled.
+fn lookup(state: &JwksState, kid: &str) -> Result<Jwk, String> {
+    state
+        .keys
+        .get(kid)
+        .cloned()
+        .ok_or_else(|| "token_invalid".to_string())
+}
+
+fn parse_max_age(header: &str) -> Option<Duration> {
+    header.split(',').find_map(|directive| {
+        let (name, value) = directive.trim().split_once('=')?;
+        if !name.eq_ignore_ascii_case("max-age") {
+            return None;
+        }
+        value.trim().parse().ok().map(Duration::from_secs)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+    use rsa::traits::PublicKeyParts;
+    use rsa::RsaPrivateKey;
+    use serde_json::{json, Value};
+
+    use super::JwksCache;
+    use crate::auth::oidc::{validate_id_token, OidcProvider};
+
+    const ISSUER: &str = "https://issuer.test";
+    const AUDIENCE: &str = "test-client";
+
+    struct SigningKey {
+        kid: String,
+        private_key_pem: String,
+        jwk: Value,
+    }
+
+    fn signing_key(kid: &str) -> SigningKey {
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        SigningKey {
+            kid: kid.to_string(),
+            private_key_pem: key.to_pkcs8_pem(LineEnding::LF).unwrap().to_string(),
+            jwk: json!({
+                "kid": kid,
+                "kty": "RSA",
+                "alg": "RS256",
+                "n": URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+            }),
+        }
+    }
+
+    fn id_token(key: &SigningKey, subject: &str) -> String {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(key.kid.clone());
+        let claims = json!({
+            "iss": ISSUER,
+            "aud": AUDIENCE,
+            "sub": subject,
+            "email": format!("{subject}@example.com"),
+            "email_verified": true,
+            "iat": now,
+            "exp": now + 300,
+        });
+        let encoding_key = EncodingKey::from_rsa_pem(key.private_key_pem.as_bytes()).unwrap();
+        encode(&header, &claims, &encoding_key).unwrap()
+    }
+
+    fn write_key_set(path: &Path, keys: &[&SigningKey]) {
+        let keys: Vec<Value> = keys.iter().map(|key| key.jwk.clone()).collect();
+        std::fs::write(path, json!({ "keys": keys }).to_string()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn validates_with_a_static_key_set_and_throttles_unknown_kids() {
+        let path = std::env::temp_dir().join(format!("jwks-{}.json", uuid::Uuid::new_v4()));
+        let provider = OidcProvider::with_jwks_uri("test", ISSUER, AUDIENCE, &format!("file://{}", path.display()));
+        let current = signing_key("k1");
+        let rotated = signing_key("k2");
+        write_key_set(&path, &[&current]);
+        let jwks = JwksCache::new(1, 3600);
+
+        let identity = validate_id_token(&provider, &jwks, &id_token(&current, "alice")).await.unwrap();
+        assert_eq!((identity.subject.as_str(), identity.email.as_str()), ("alice", "alice@example.com"));
+
+        // A token signed by another key under a known kid fails on the signature, not the lookup.
+        let mut forged = signing_key("k1");
+        forged.jwk = current.jwk.clone();
+        let result = validate_id_token(&provider, &jwks, &id_token(&forged, "mallory")).await;
+        assert_eq!(result.err().as_deref(), Some("token_invalid"));
+
+        // The issuer rotates right after the first read. Within the refresh window an unknown kid
+        // does not reread the set, however often it is presented.
+        write_key_set(&path, &[&current, &rotated]);
+        for _ in 0..3 {
+            let result = validate_id_token(&provider, &jwks, &id_token(&rotated, "bob")).await;
+            assert_eq!(result.err().as_deref(), Some("token_invalid"));
+        }
+        assert!(validate_id_token(&provider, &jwks, &id_token(&current, "alice")).await.is_ok());
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let identity = validate_id_token(&provider, &jwks, &id_token(&rotated, "bob")).await.unwrap();
+        assert_eq!(identity.subject, "bob");
+        std::fs::remove_file(&path).ok();
+    }
+}
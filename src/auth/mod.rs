@@ -1,2 +1,6 @@
+pub mod api_tokens;
 pub mod google;
+pub mod jwks;
+pub mod oidc;
+pub mod provider;
 pub mod sessions;
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{Mutex, RwLock};
+
+use crate::auth::google;
+use crate::auth::jwks::JwksCache;
+use crate::auth::oidc::OidcProvider;
+use crate::config::{Config, OidcProviderConfig};
+
+/// An OpenID Connect issuer whose ID tokens can sign users in.
+pub trait IdentityProvider: Send + Sync {
+    /// Stable name stored in user_identities.provider, e.g. "google".
+    fn id(&self) -> &str;
+    /// Accepted `iss` claim values.
+    fn issuers(&self) -> &[String];
+    /// Expected `aud` claim (the OAuth client id).
+    fn audience(&self) -> &str;
+    /// Where the signing keys are published.
+    fn jwks_uri(&self) -> &str;
+    fn require_verified_email(&self) -> bool {
+        true
+    }
+}
+
+/// Subject and email extracted from a validated ID token.
+pub struct Identity {
+    pub provider: String,
+    pub subject: String,
+    pub email: String,
+}
+
+/// Minimum gap between discovery attempts for an issuer that was unreachable.
+const DISCOVERY_RETRY_SECS: u64 = 30;
+
+/// A configured issuer whose discovery has not succeeded yet.
+struct PendingDiscovery {
+    entry: OidcProviderConfig,
+    last_attempt: Instant,
+}
+
+#[derive(Clone)]
+pub struct IdentityProviders {
+    providers: Arc<RwLock<HashMap<String, Arc<dyn IdentityProvider>>>>,
+    pending: Arc<Mutex<HashMap<String, PendingDiscovery>>>,
+    jwks: Arc<JwksCache>,
+}
+
+impl IdentityProviders {
+    /// Registers Google plus every issuer listed in OIDC_PROVIDERS, running discovery unless a JWKS URI is pinned.
+    /// An issuer whose discovery fails does not stop startup; it is retried when someone signs in with it.
+    pub async fn from_config(config: &Config) -> Self {
+        let providers = Self {
+            providers: Arc::new(RwLock::new(HashMap::new())),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            jwks: Arc::new(JwksCache::new(
+                config.jwks_min_refresh_secs,
+                config.jwks_default_ttl_secs,
+            )),
+        };
+        providers.register(Arc::new(google::provider(config))).await;
+        for entry in &config.oidc_providers {
+            match &entry.jwks_uri {
+                Some(jwks_uri) => {
+                    let provider =
+                        OidcProvider::with_jwks_uri(&entry.id, &entry.issuer, &entry.client_id, jwks_uri);
+                    providers.register(Arc::new(provider)).await;
+                }
+                None => match OidcProvider::discover(&entry.id, &entry.issuer, &entry.client_id).await {
+                    Ok(provider) => providers.register(Arc::new(provider)).await,
+                    Err(err) => {
+                        log::warn!("{err}; retrying on first sign-in");
+                        let pending = PendingDiscovery { entry: entry.clone(), last_attempt: Instant::now() };
+                        providers.pending.lock().await.insert(entry.id.clone(), pending);
+                    }
+                },
+            }
+        }
+        providers
+    }
+
+    pub async fn register(&self, provider: Arc<dyn IdentityProvider>) {
+        self.providers.write().await.insert(provider.id().to_string(), provider);
+    }
+
+    /// Looks up a provider, retrying discovery for issuers that were unreachable.
+    /// `Err` means the provider is configured but its discovery still fails.
+    pub async fn get(&self, id: &str) -> Result<Option<Arc<dyn IdentityProvider>>, String> {
+        if let Some(provider) = self.providers.read().await.get(id).cloned() {
+            return Ok(Some(provider));
+        }
+        let mut pending = self.pending.lock().await;
+        let Some(attempt) = pending.get_mut(id) else {
+            return Ok(None);
+        };
+        if attempt.last_attempt.elapsed() < Duration::from_secs(DISCOVERY_RETRY_SECS) {
+            return Err(format!("discovery for {id} is backing off"));
+        }
+        attempt.last_attempt = Instant::now();
+        let entry = &attempt.entry;
+        let provider: Arc<dyn IdentityProvider> =
+            Arc::new(OidcProvider::discover(&entry.id, &entry.issuer, &entry.client_id).await?);
+        pending.remove(id);
+        self.register(provider.clone()).await;
+        Ok(Some(provider))
+    }
+
+    pub fn jwks(&self) -> &JwksCache {
+        &self.jwks
+    }
+}
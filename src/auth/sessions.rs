@@ -1,7 +1,10 @@
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::RngCore;
+use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 
+use crate::config::Config;
+
 pub fn generate_token() -> String {
     let mut bytes = [0u8; 32];
     rand::rngs::OsRng.fill_bytes(&mut bytes);
@@ -11,3 +14,36 @@ pub fn generate_token() -> String {
 pub fn expires_at(ttl_secs: i64) -> OffsetDateTime {
     OffsetDateTime::now_utc() + time::Duration::seconds(ttl_secs)
 }
+
+/// Sessions are keyed by this digest so stored rows cannot be replayed as bearer tokens.
+pub fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+/// Sliding-expiry settings applied on every authenticated lookup.
+#[derive(Clone, Copy)]
+pub struct SessionLifetime {
+    pub ttl_secs: i64,
+    pub renew_interval_secs: i64,
+    pub max_lifetime_secs: i64,
+}
+
+impl SessionLifetime {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ttl_secs: config.session_ttl_secs,
+            renew_interval_secs: config.session_renew_interval_secs,
+            max_lifetime_secs: config.session_max_lifetime_secs,
+        }
+    }
+
+    /// Expiry for a freshly issued session, never past the absolute lifetime.
+    pub fn session_expires_at(&self) -> OffsetDateTime {
+        expires_at(self.ttl_secs.min(self.max_lifetime_secs))
+    }
+
+    /// Refresh tokens may only be redeemed until the absolute lifetime ends.
+    pub fn refresh_expires_at(&self) -> OffsetDateTime {
+        expires_at(self.max_lifetime_secs)
+    }
+}
@@ -1,14 +1,37 @@
 use std::env;
 
+use crate::auth::google;
+
+#[derive(Clone)]
+pub struct OidcProviderConfig {
+    pub id: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub jwks_uri: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct Config {
     pub bind_addr: String,
     pub database_url: String,
     pub google_client_id: String,
+    pub google_jwks_uri: String,
+    pub google_issuers: Vec<String>,
+    pub jwks_min_refresh_secs: u64,
+    pub jwks_default_ttl_secs: u64,
+    pub oidc_providers: Vec<OidcProviderConfig>,
     pub public_base_url: String,
     pub session_ttl_secs: i64,
+    pub session_renew_interval_secs: i64,
+    pub session_max_lifetime_secs: i64,
+    pub sweep_interval_secs: u64,
     pub run_migrations: bool,
     pub migrations_path: String,
+    pub activitypub_enabled: bool,
+    pub activitypub_allow_insecure: bool,
+    pub activitypub_poll_interval_secs: u64,
+    pub webhook_poll_interval_secs: u64,
+    pub webhook_allow_insecure: bool,
 }
 
 impl Config {
@@ -16,31 +39,114 @@ impl Config {
         let bind_addr = env::var("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
         let database_url = env::var("DATABASE_URL")?;
         let google_client_id = env::var("GOOGLE_CLIENT_ID")?;
+        let google_jwks_uri = env::var("GOOGLE_JWKS_URI")
+            .unwrap_or_else(|_| google::DEFAULT_JWKS_URI.to_string());
+        let google_issuers = env::var("GOOGLE_ISSUERS")
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|_| google::DEFAULT_ISSUERS.iter().map(|s| s.to_string()).collect());
+        let jwks_min_refresh_secs = env::var("JWKS_MIN_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+        let jwks_default_ttl_secs = env::var("JWKS_DEFAULT_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600);
+        let oidc_providers = parse_oidc_providers()?;
         let public_base_url = env::var("PUBLIC_BASE_URL")?;
         let public_base_url = normalize_base_url(public_base_url)?;
         let session_ttl_secs = env::var("SESSION_TTL_SECS")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(3600);
-        let run_migrations = env::var("RUN_MIGRATIONS")
+        let session_renew_interval_secs = env::var("SESSION_RENEW_INTERVAL_SECS")
             .ok()
-            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-            .unwrap_or(false);
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        let session_max_lifetime_secs = env::var("SESSION_MAX_LIFETIME_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30 * 24 * 3600);
+        let sweep_interval_secs = env::var("SWEEP_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(600);
+        let run_migrations = env_flag("RUN_MIGRATIONS");
         let migrations_path = env::var("MIGRATIONS_PATH")
             .unwrap_or_else(|_| "db/migrations".to_string());
+        let activitypub_enabled = env_flag("ACTIVITYPUB_ENABLED");
+        // Lets a local stub peer on plain http://localhost take part in development setups.
+        let activitypub_allow_insecure = env_flag("ACTIVITYPUB_ALLOW_INSECURE");
+        let activitypub_poll_interval_secs = env::var("ACTIVITYPUB_POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let webhook_poll_interval_secs = env::var("WEBHOOK_POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        // Allows plain http:// endpoints and private addresses, such as a receiver on localhost.
+        let webhook_allow_insecure = env_flag("WEBHOOK_ALLOW_INSECURE");
 
         Ok(Self {
             bind_addr,
             database_url,
             google_client_id,
+            google_jwks_uri,
+            google_issuers,
+            jwks_min_refresh_secs,
+            jwks_default_ttl_secs,
+            oidc_providers,
             public_base_url,
             session_ttl_secs,
+            session_renew_interval_secs,
+            session_max_lifetime_secs,
+            sweep_interval_secs,
             run_migrations,
             migrations_path,
+            activitypub_enabled,
+            activitypub_allow_insecure,
+            activitypub_poll_interval_secs,
+            webhook_poll_interval_secs,
+            webhook_allow_insecure,
         })
     }
 }
 
+/// OIDC_PROVIDERS=keycloak,mock reads OIDC_KEYCLOAK_ISSUER / OIDC_KEYCLOAK_CLIENT_ID and so on;
+/// an optional OIDC_<NAME>_JWKS_URI skips discovery.
+fn parse_oidc_providers() -> Result<Vec<OidcProviderConfig>, env::VarError> {
+    let Ok(names) = env::var("OIDC_PROVIDERS") else {
+        return Ok(Vec::new());
+    };
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let prefix = format!("OIDC_{}", name.to_ascii_uppercase());
+            Ok(OidcProviderConfig {
+                id: name.to_ascii_lowercase(),
+                issuer: env::var(format!("{prefix}_ISSUER"))?,
+                client_id: env::var(format!("{prefix}_CLIENT_ID"))?,
+                jwks_uri: env::var(format!("{prefix}_JWKS_URI")).ok(),
+            })
+        })
+        .collect()
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .ok()
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
 fn normalize_base_url(value: String) -> Result<String, env::VarError> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -0,0 +1,117 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+use uuid::Uuid;
+
+use crate::storage::StorageError;
+
+const BATCH_SIZE: i64 = 20;
+/// How long a claimed delivery stays hidden from other dispatchers before it counts as abandoned.
+const LEASE_SECS: i32 = 300;
+const MAX_ATTEMPTS: i32 = 10;
+const BASE_BACKOFF_SECS: i32 = 30;
+const MAX_BACKOFF_SECS: i32 = 6 * 3600;
+const MAX_ERROR_CHARS: usize = 500;
+
+/// A claimed outbox row: its id and how many attempts it has had, counting the current one.
+pub trait Claimed: Send + Sync + 'static {
+    fn id(&self) -> Uuid;
+    fn attempts(&self) -> i32;
+}
+
+/// A table of queued deliveries that [`spawn_dispatcher`] claims, sends and settles.
+pub trait Outbox: Clone + Send + Sync + 'static {
+    type Delivery: Claimed;
+
+    /// Names the outbox in log lines.
+    const NAME: &'static str;
+
+    fn claim(
+        &self,
+        limit: i64,
+        lease_secs: i32,
+    ) -> impl Future<Output = Result<Vec<Self::Delivery>, StorageError>> + Send;
+
+    /// Makes one attempt and returns the response status; `Err` means no response arrived.
+    fn send(&self, delivery: &Self::Delivery) -> impl Future<Output = Result<u16, String>> + Send;
+
+    fn complete(&self, id: Uuid, status_code: i32) -> impl Future<Output = Result<(), StorageError>> + Send;
+
+    /// Records a failed attempt; without `retry_in_secs` the delivery is given up as failed.
+    fn fail(
+        &self,
+        id: Uuid,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_secs: Option<i32>,
+    ) -> impl Future<Output = Result<(), StorageError>> + Send;
+}
+
+/// Drains `outbox` every `interval_secs` until `shutdown` flips. Due rows are claimed in batches
+/// with FOR UPDATE SKIP LOCKED, so several instances can share one outbox.
+pub fn spawn_dispatcher<O: Outbox>(
+    outbox: O,
+    interval_secs: u64,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            // Keep claiming while batches come back full, so a backlog drains without waiting a tick.
+            loop {
+                let batch = match outbox.claim(BATCH_SIZE, LEASE_SECS).await {
+                    Ok(batch) => batch,
+                    Err(err) => {
+                        log::error!("{} dispatcher failed: {err}", O::NAME);
+                        break;
+                    }
+                };
+                let full = batch.len() as i64 == BATCH_SIZE;
+                let mut sends = JoinSet::new();
+                for delivery in batch {
+                    sends.spawn(attempt(outbox.clone(), delivery));
+                }
+                while sends.join_next().await.is_some() {}
+                if !full {
+                    break;
+                }
+            }
+        }
+        log::info!("{} dispatcher stopped", O::NAME);
+    })
+}
+
+async fn attempt<O: Outbox>(outbox: O, delivery: O::Delivery) {
+    let id = delivery.id();
+    let retry = retry_delay(delivery.attempts());
+    let recorded = match outbox.send(&delivery).await {
+        Ok(status) if (200..300).contains(&status) => outbox.complete(id, i32::from(status)).await,
+        Ok(status) => {
+            outbox
+                .fail(id, Some(i32::from(status)), &format!("HTTP {status}"), retry)
+                .await
+        }
+        Err(err) => {
+            let error: String = err.chars().take(MAX_ERROR_CHARS).collect();
+            outbox.fail(id, None, &error, retry).await
+        }
+    };
+    if let Err(err) = recorded {
+        log::error!("{} delivery {id} could not be recorded: {err}", O::NAME);
+    }
+}
+
+/// Exponential backoff from 30 seconds, capped at 6 hours; `None` once attempts are exhausted.
+fn retry_delay(attempts: i32) -> Option<i32> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    let exponent = attempts.clamp(1, 16) - 1;
+    Some(BASE_BACKOFF_SECS.saturating_mul(1 << exponent).min(MAX_BACKOFF_SECS))
+}
@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+use crate::domain::{AccountProfile, Association, FollowEdge, Note, RemoteFollower, UserIdentity};
+
+/// One row of an account export, serialized as an ndjson line: {"type": …, "data": …}.
+/// Storage emits the profile first, then each kind in declaration order.
+#[derive(Serialize, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ExportRecord {
+    Profile {
+        profile: AccountProfile,
+        created_at: String,
+    },
+    Identity(UserIdentity),
+    AccountNote(Note),
+    Note(Note),
+    Association(Association),
+    Follower(FollowEdge),
+    RemoteFollower(RemoteFollower),
+    Following(FollowEdge),
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, Clone)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
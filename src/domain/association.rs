@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Association {
@@ -7,3 +8,11 @@ pub struct Association {
     pub to_id: String,
     pub created_at: String,
 }
+
+/// An association its source owner removed, kept so related views can explain the missing link.
+#[derive(Serialize, Clone)]
+pub struct AssociationRemoval {
+    pub association: Association,
+    pub removed_by: Uuid,
+    pub removed_at: String,
+}
@@ -0,0 +1,17 @@
+use uuid::Uuid;
+
+/// A local user's key pair for signing ActivityPub deliveries.
+#[derive(Clone)]
+pub struct ActorKey {
+    pub public_key_pem: String,
+    pub private_key_pem: String,
+}
+
+/// An actor on another server, as last fetched from its actor document.
+#[derive(Clone)]
+pub struct RemoteActor {
+    pub id: Uuid,
+    pub actor_uri: String,
+    pub inbox_url: String,
+    pub public_key_pem: String,
+}
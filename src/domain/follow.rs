@@ -14,3 +14,10 @@ pub struct FollowEdge {
     pub user: UserProfile,
     pub created_at: String,
 }
+
+/// A follower on another ActivityPub server, known only by its actor URI.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RemoteFollower {
+    pub actor_uri: String,
+    pub created_at: String,
+}
@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone)]
+pub struct UserIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub email: String,
+    pub created_at: String,
+}
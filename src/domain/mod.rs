@@ -1,9 +1,29 @@
+pub mod account;
+pub mod api_token;
 pub mod association;
+pub mod federation;
 pub mod follow;
+pub mod identity;
 pub mod note;
+pub mod notification;
+pub mod page;
+pub mod session;
+pub mod stream;
+pub mod thread;
 pub mod user;
+pub mod webhook;
 
-pub use association::Association;
-pub use follow::{Follow, FollowEdge};
+pub use account::ExportRecord;
+pub use api_token::ApiToken;
+pub use association::{Association, AssociationRemoval};
+pub use federation::{ActorKey, RemoteActor};
+pub use follow::{Follow, FollowEdge, RemoteFollower};
+pub use identity::UserIdentity;
 pub use note::{generate_note_id, Note, NoteId};
-pub use user::{User, UserProfile};
+pub use notification::Notification;
+pub use page::{Cursor, Page};
+pub use session::Session;
+pub use stream::StreamEvent;
+pub use thread::ThreadEdge;
+pub use user::{default_handle, is_reserved_handle, normalize_handle, AccountProfile, User, UserProfile};
+pub use webhook::{Webhook, WebhookDelivery, WEBHOOK_EVENTS};
@@ -13,6 +13,14 @@ pub struct Note {
     pub value: String,
     pub created_at: String,
     pub author: UserProfile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retracted_at: Option<String>,
+}
+
+impl Note {
+    pub fn is_retracted(&self) -> bool {
+        self.retracted_at.is_some()
+    }
 }
 
 impl NoteId {
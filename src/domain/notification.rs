@@ -0,0 +1,19 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domain::note::Note;
+use crate::domain::user::UserProfile;
+
+#[derive(Serialize, Clone)]
+pub struct Notification {
+    pub id: Uuid,
+    /// reply, quote, follow or version.
+    pub kind: String,
+    pub actor: UserProfile,
+    /// The reply, quote or new version; absent for follows.
+    pub note: Option<Note>,
+    /// The recipient's note that was replied to, quoted or superseded.
+    pub target_id: Option<String>,
+    pub read: bool,
+    pub created_at: String,
+}
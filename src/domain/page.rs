@@ -0,0 +1,57 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+#[derive(Serialize, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Cursor {
+    pub created_at: OffsetDateTime,
+    pub key: String,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let created_at = self
+            .created_at
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string());
+        URL_SAFE_NO_PAD.encode(format!("{created_at}|{}", self.key))
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value.as_bytes()).ok()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        let (created_at, key) = raw.split_once('|')?;
+        let created_at = OffsetDateTime::parse(created_at, &Rfc3339).ok()?;
+        if key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            created_at,
+            key: key.to_string(),
+        })
+    }
+}
+
+impl<T> Page<T> {
+    /// Builds a page from `limit + 1` fetched rows; the extra row only signals that more exist.
+    pub fn from_rows(mut rows: Vec<T>, limit: usize, cursor_of: impl Fn(&T) -> Option<Cursor>) -> Self {
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = if has_more {
+            rows.last().and_then(cursor_of).map(|cursor| cursor.encode())
+        } else {
+            None
+        };
+        Self {
+            items: rows,
+            next_cursor,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, Clone)]
+pub struct Session {
+    pub id: Uuid,
+    pub created_at: String,
+    pub expires_at: String,
+    pub current: bool,
+}
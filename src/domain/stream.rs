@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::Association;
+
+/// A change notice broadcast to live-update subscribers. It carries ids only; subscribers load
+/// the notes they need, which keeps NOTIFY payloads far below PostgreSQL's 8000-byte limit.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// A new post (chain head); `supersedes` is set when it is a new version of an older post.
+    Post {
+        note_id: String,
+        author_id: Uuid,
+        supersedes: Option<String>,
+    },
+    Association(Association),
+    Follow {
+        follower_id: Uuid,
+        followee_id: Uuid,
+        following: bool,
+    },
+}
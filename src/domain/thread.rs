@@ -0,0 +1,11 @@
+use crate::domain::association::Association;
+use crate::domain::note::Note;
+
+/// One reply or quote in a thread walk: `note` is the association's other end from where the
+/// walk came from, and `reply_count` counts every reply and quote to it, shown or not.
+#[derive(Clone)]
+pub struct ThreadEdge {
+    pub association: Association,
+    pub note: Note,
+    pub reply_count: i64,
+}
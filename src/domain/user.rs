@@ -4,15 +4,35 @@ use uuid::Uuid;
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UserProfile {
     pub user_id: Uuid,
-    pub email: String,
+    pub handle: String,
+    pub display_name: Option<String>,
     pub account_note_id: Option<String>,
 }
 
+impl UserProfile {
+    /// Public author label: "Display Name (@handle)", or "@handle" without a display name.
+    pub fn label(&self) -> String {
+        match self.display_name.as_deref() {
+            Some(name) => format!("{name} (@{})", self.handle),
+            None => format!("@{}", self.handle),
+        }
+    }
+}
+
+/// The signed-in user's own view of their profile; the only place email is exposed.
+#[derive(Serialize, Clone)]
+pub struct AccountProfile {
+    #[serde(flatten)]
+    pub profile: UserProfile,
+    pub email: String,
+}
+
 #[derive(Clone)]
 pub struct User {
     pub user_id: Uuid,
-    pub google_sub: String,
     pub email: String,
+    pub handle: String,
+    pub display_name: Option<String>,
     pub account_note_id: Option<String>,
 }
 
@@ -20,8 +40,36 @@ impl User {
     pub fn profile(&self) -> UserProfile {
         UserProfile {
             user_id: self.user_id,
-            email: self.email.clone(),
+            handle: self.handle.clone(),
+            display_name: self.display_name.clone(),
             account_note_id: self.account_note_id.clone(),
         }
     }
+
+    pub fn account_profile(&self) -> AccountProfile {
+        AccountProfile {
+            profile: self.profile(),
+            email: self.email.clone(),
+        }
+    }
+}
+
+/// Handle assigned at sign-up, derived from the user id so it is unique without a lookup.
+pub fn default_handle(user_id: Uuid) -> String {
+    format!("user_{}", &user_id.simple().to_string()[..12])
+}
+
+/// Handles of the default_handle shape belong to one user id; account deletion resets to them.
+pub fn is_reserved_handle(handle: &str, user_id: Uuid) -> bool {
+    handle.starts_with("user_") && handle != default_handle(user_id)
+}
+
+/// Lowercases a requested handle and checks it is 3-30 characters of [a-z0-9_].
+pub fn normalize_handle(value: &str) -> Option<String> {
+    let handle = value.trim().to_ascii_lowercase();
+    let valid = (3..=30).contains(&handle.len())
+        && handle
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    valid.then_some(handle)
 }
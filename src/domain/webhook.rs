@@ -0,0 +1,35 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Event names a webhook can subscribe to.
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    "note.created",
+    "note.versioned",
+    "association.created",
+    "association.deleted",
+    "follow.created",
+    "follow.deleted",
+];
+
+#[derive(Serialize, Clone)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub events: Vec<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub event: String,
+    pub payload: serde_json::Value,
+    /// pending, delivered or failed (retries exhausted).
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<String>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<String>,
+    pub created_at: String,
+}
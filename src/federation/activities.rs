@@ -0,0 +1,140 @@
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::domain::{Note, UserProfile};
+
+pub const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+const CONTEXT: [&str; 2] = [
+    "https://www.w3.org/ns/activitystreams",
+    "https://w3id.org/security/v1",
+];
+
+pub fn actor_uri(base_url: &str, user_id: Uuid) -> String {
+    format!("{base_url}/users/{user_id}")
+}
+
+pub fn key_id(actor_uri: &str) -> String {
+    format!("{actor_uri}#main-key")
+}
+
+pub fn note_uri(base_url: &str, note_id: &str) -> String {
+    format!("{base_url}/notes/{note_id}")
+}
+
+/// `acct:` subject for a handle, e.g. `acct:alice@lgxpkf.example`.
+pub fn acct(handle: &str, host: &str) -> String {
+    format!("acct:{handle}@{host}")
+}
+
+pub fn webfinger(base_url: &str, host: &str, profile: &UserProfile) -> Value {
+    let actor = actor_uri(base_url, profile.user_id);
+    let profile_url = format!("{base_url}/@{}", profile.handle);
+    json!({
+        "subject": acct(&profile.handle, host),
+        "aliases": [actor, profile_url],
+        "links": [
+            { "rel": "self", "type": "application/activity+json", "href": actor },
+            { "rel": "http://webfinger.net/rel/profile-page", "type": "text/html", "href": profile_url },
+        ],
+    })
+}
+
+pub fn actor_document(
+    base_url: &str,
+    profile: &UserProfile,
+    summary_html: Option<&str>,
+    public_key_pem: &str,
+) -> Value {
+    let actor = actor_uri(base_url, profile.user_id);
+    without_nulls(json!({
+        "@context": CONTEXT,
+        "id": actor,
+        "type": "Person",
+        "preferredUsername": profile.handle,
+        "name": profile.label(),
+        "summary": summary_html,
+        "url": format!("{base_url}/@{}", profile.handle),
+        "inbox": format!("{actor}/inbox"),
+        "outbox": format!("{actor}/outbox"),
+        "followers": format!("{actor}/followers"),
+        "publicKey": {
+            "id": key_id(&actor),
+            "owner": actor,
+            "publicKeyPem": public_key_pem,
+        },
+    }))
+}
+
+/// A post as a Note object; `in_reply_to` is the object id of the note a `reply` association targets.
+pub fn note_object(base_url: &str, note: &Note, content_html: &str, in_reply_to: Option<&str>) -> Value {
+    let actor = actor_uri(base_url, note.author.user_id);
+    without_nulls(json!({
+        "id": note_uri(base_url, &note.id),
+        "type": "Note",
+        "attributedTo": actor,
+        "content": content_html,
+        "url": format!("{base_url}/{}", note.id),
+        "published": note.created_at,
+        "inReplyTo": in_reply_to,
+        "to": [PUBLIC],
+        "cc": [format!("{actor}/followers")],
+    }))
+}
+
+/// Wraps a Note in a Create or Update activity addressed like the object itself.
+pub fn wrap_object(kind: &str, object: Value, published: &str) -> Value {
+    let object_id = object["id"].as_str().unwrap_or_default();
+    let id = match kind {
+        "Create" => format!("{object_id}#create"),
+        _ => format!("{object_id}#{}-{published}", kind.to_ascii_lowercase()),
+    };
+    json!({
+        "@context": CONTEXT,
+        "id": id,
+        "type": kind,
+        "actor": object["attributedTo"],
+        "published": published,
+        "to": object["to"],
+        "cc": object["cc"],
+        "object": object,
+    })
+}
+
+pub fn accept_follow(actor: &str, follow: &Value) -> Value {
+    json!({
+        "@context": CONTEXT,
+        "id": format!("{actor}#accepts/{}", Uuid::new_v4()),
+        "type": "Accept",
+        "actor": actor,
+        "object": follow,
+    })
+}
+
+pub fn ordered_collection(id: &str, total_items: Option<i64>, first: Option<&str>) -> Value {
+    without_nulls(json!({
+        "@context": CONTEXT,
+        "id": id,
+        "type": "OrderedCollection",
+        "totalItems": total_items,
+        "first": first,
+    }))
+}
+
+pub fn ordered_collection_page(id: &str, part_of: &str, items: Vec<Value>, next: Option<&str>) -> Value {
+    without_nulls(json!({
+        "@context": CONTEXT,
+        "id": id,
+        "type": "OrderedCollectionPage",
+        "partOf": part_of,
+        "orderedItems": items,
+        "next": next,
+    }))
+}
+
+/// Optional properties are omitted rather than sent as null.
+fn without_nulls(mut value: Value) -> Value {
+    if let Some(object) = value.as_object_mut() {
+        object.retain(|_, field| !field.is_null());
+    }
+    value
+}
@@ -0,0 +1,41 @@
+use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::RsaPrivateKey;
+
+use uuid::Uuid;
+
+use crate::domain::ActorKey;
+use crate::storage::Storage;
+
+const KEY_BITS: usize = 2048;
+
+/// Generates an RSA key pair as PEM. Slow enough that callers run it on the blocking pool.
+pub fn generate_actor_key() -> Result<ActorKey, String> {
+    let private_key =
+        RsaPrivateKey::new(&mut rand::thread_rng(), KEY_BITS).map_err(|err| err.to_string())?;
+    let public_key_pem = private_key
+        .to_public_key()
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|err| err.to_string())?;
+    let private_key_pem = private_key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|err| err.to_string())?
+        .to_string();
+    Ok(ActorKey {
+        public_key_pem,
+        private_key_pem,
+    })
+}
+
+/// Loads a user's actor key, generating and storing one on first use.
+pub async fn ensure_actor_key(storage: &Storage, user_id: Uuid) -> Result<ActorKey, String> {
+    if let Some(key) = storage.find_actor_key(user_id).await.map_err(|err| err.to_string())? {
+        return Ok(key);
+    }
+    let key = tokio::task::spawn_blocking(generate_actor_key)
+        .await
+        .map_err(|err| err.to_string())??;
+    storage
+        .insert_actor_key(user_id, &key)
+        .await
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,184 @@
+pub mod activities;
+pub mod keys;
+pub mod outbox;
+pub mod signatures;
+
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use serde_json::Value;
+use url::Url;
+
+use crate::config::Config;
+use crate::federation::signatures::{digest_header, sign, signing_string, SIGNED_HEADERS};
+use crate::outbound;
+
+pub const ACTIVITY_JSON: &str = "application/activity+json";
+const FETCH_ACCEPT: &str =
+    "application/activity+json, application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"";
+const MAX_DOCUMENT_BYTES: usize = 1024 * 1024;
+
+/// The parts of a remote actor document needed to verify its signatures and deliver to it.
+pub struct ActorDocument {
+    pub id: String,
+    pub inbox: String,
+    pub shared_inbox: Option<String>,
+    pub key_id: String,
+    pub public_key_pem: String,
+}
+
+/// Outbound HTTP for ActivityPub: actor fetches and signed deliveries.
+#[derive(Clone)]
+pub struct Federation {
+    client: Client,
+    allow_insecure: bool,
+}
+
+impl Federation {
+    pub fn from_config(config: &Config) -> Self {
+        let builder = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .redirect(Policy::none())
+            .user_agent(concat!("lgxpkf/", env!("CARGO_PKG_VERSION")));
+        let client = outbound::guard(builder, config.activitypub_allow_insecure)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            allow_insecure: config.activitypub_allow_insecure,
+        }
+    }
+
+    /// Remote URLs must be https on a public address unless insecure peers are allowed for local
+    /// testing. Names are checked again when the client resolves them.
+    pub fn check_url(&self, raw: &str) -> Result<Url, String> {
+        let url = Url::parse(raw).map_err(|err| err.to_string())?;
+        if self.allow_insecure {
+            return match url.scheme() {
+                "https" | "http" => Ok(url),
+                scheme => Err(format!("unsupported scheme {scheme}")),
+            };
+        }
+        if url.scheme() != "https" {
+            return Err(format!("unsupported scheme {}", url.scheme()));
+        }
+        outbound::check_literal_host(&url)?;
+        Ok(url)
+    }
+
+    /// Resolves a signature keyId to its owning actor. The key is either a fragment of the actor
+    /// document (Mastodon's `…#main-key`) or a standalone key document naming its `owner`.
+    pub async fn fetch_actor(&self, key_id: &str) -> Result<ActorDocument, String> {
+        let mut key_url = self.check_url(key_id)?;
+        key_url.set_fragment(None);
+        let mut document = self.fetch_document(&key_url).await?;
+        if document.get("inbox").is_none() {
+            let owner = document["owner"].as_str().ok_or("key document has no owner")?;
+            let owner_url = self.check_url(owner)?;
+            if owner_url.host_str() != key_url.host_str() {
+                return Err("key owner on a different host".to_string());
+            }
+            document = self.fetch_document(&owner_url).await?;
+        }
+        let id = document["id"].as_str().ok_or("actor has no id")?;
+        if self.check_url(id)?.host_str() != key_url.host_str() {
+            return Err("actor id on a different host".to_string());
+        }
+        // Some servers publish a list of keys; take the one that was asked for.
+        let public_key = match &document["publicKey"] {
+            Value::Array(keys) => keys.iter().find(|key| key["id"] == key_id).cloned(),
+            key @ Value::Object(_) => Some(key.clone()),
+            _ => None,
+        }
+        .ok_or("actor has no public key")?;
+        if public_key["id"].as_str() != Some(key_id) || public_key["owner"].as_str() != Some(id) {
+            return Err("public key does not belong to the actor".to_string());
+        }
+        let inbox = document["inbox"].as_str().ok_or("actor has no inbox")?;
+        let shared_inbox = document["endpoints"]["sharedInbox"].as_str();
+        Ok(ActorDocument {
+            id: id.to_string(),
+            inbox: self.check_url(inbox)?.to_string(),
+            shared_inbox: shared_inbox
+                .and_then(|url| self.check_url(url).ok())
+                .map(|url| url.to_string()),
+            key_id: key_id.to_string(),
+            public_key_pem: public_key["publicKeyPem"]
+                .as_str()
+                .ok_or("public key has no PEM")?
+                .to_string(),
+        })
+    }
+
+    /// POSTs an activity to an inbox, signed with the sending actor's key, and returns the response
+    /// status; `Err` means no response arrived.
+    pub async fn deliver(
+        &self,
+        inbox: &str,
+        key_id: &str,
+        private_key_pem: &str,
+        activity: &Value,
+    ) -> Result<u16, String> {
+        let url = self.check_url(inbox)?;
+        let body = serde_json::to_vec(activity).map_err(|err| err.to_string())?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let target = match url.query() {
+            Some(query) => format!("post {}?{query}", url.path()),
+            None => format!("post {}", url.path()),
+        };
+        let date = httpdate::fmt_http_date(SystemTime::now());
+        let digest = digest_header(&body);
+        let headers: Vec<String> = SIGNED_HEADERS.iter().map(|name| name.to_string()).collect();
+        let to_sign = signing_string(&headers, |name| match name {
+            "(request-target)" => Some(target.clone()),
+            "host" => Some(host.clone()),
+            "date" => Some(date.clone()),
+            "digest" => Some(digest.clone()),
+            _ => None,
+        })
+        .ok_or("unsigned header")?;
+        let signature = format!(
+            "keyId=\"{key_id}\",algorithm=\"rsa-sha256\",headers=\"{}\",signature=\"{}\"",
+            headers.join(" "),
+            sign(private_key_pem, &to_sign)?
+        );
+        let response = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, ACTIVITY_JSON)
+            .header("Date", date)
+            .header("Digest", digest)
+            .header("Signature", signature)
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(response.status().as_u16())
+    }
+
+    async fn fetch_document(&self, url: &Url) -> Result<Value, String> {
+        let response = self
+            .client
+            .get(url.clone())
+            .header(ACCEPT, FETCH_ACCEPT)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("{url} returned {}", response.status()));
+        }
+        if response.content_length().is_some_and(|len| len > MAX_DOCUMENT_BYTES as u64) {
+            return Err(format!("{url} is too large"));
+        }
+        let bytes = response.bytes().await.map_err(|err| err.to_string())?;
+        if bytes.len() > MAX_DOCUMENT_BYTES {
+            return Err(format!("{url} is too large"));
+        }
+        serde_json::from_slice(&bytes).map_err(|err| err.to_string())
+    }
+}
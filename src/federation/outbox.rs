@@ -0,0 +1,77 @@
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::dispatch::{spawn_dispatcher, Claimed, Outbox};
+use crate::federation::activities::{actor_uri, key_id};
+use crate::federation::keys::ensure_actor_key;
+use crate::federation::Federation;
+use crate::storage::{DueActivity, Storage, StorageError};
+
+/// Signs and sends queued ActivityPub deliveries every `interval_secs` until `shutdown` flips.
+pub fn spawn_federation_dispatcher(
+    storage: Storage,
+    federation: Federation,
+    public_base_url: String,
+    interval_secs: u64,
+    shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    let outbox = FederationOutbox {
+        storage,
+        federation,
+        public_base_url,
+    };
+    spawn_dispatcher(outbox, interval_secs, shutdown)
+}
+
+#[derive(Clone)]
+struct FederationOutbox {
+    storage: Storage,
+    federation: Federation,
+    public_base_url: String,
+}
+
+impl Claimed for DueActivity {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn attempts(&self) -> i32 {
+        self.attempts
+    }
+}
+
+impl Outbox for FederationOutbox {
+    type Delivery = DueActivity;
+
+    const NAME: &'static str = "federation";
+
+    async fn claim(&self, limit: i64, lease_secs: i32) -> Result<Vec<DueActivity>, StorageError> {
+        self.storage.claim_federation_deliveries(limit, lease_secs).await
+    }
+
+    /// Signs with the author's key at send time, so the Date header is fresh on every retry.
+    async fn send(&self, delivery: &DueActivity) -> Result<u16, String> {
+        let key = ensure_actor_key(&self.storage, delivery.user_id).await?;
+        let key_id = key_id(&actor_uri(&self.public_base_url, delivery.user_id));
+        self.federation
+            .deliver(&delivery.inbox_url, &key_id, &key.private_key_pem, &delivery.activity)
+            .await
+    }
+
+    async fn complete(&self, id: Uuid, status_code: i32) -> Result<(), StorageError> {
+        self.storage.complete_federation_delivery(id, status_code).await
+    }
+
+    async fn fail(
+        &self,
+        id: Uuid,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_secs: Option<i32>,
+    ) -> Result<(), StorageError> {
+        self.storage
+            .fail_federation_delivery(id, status_code, error, retry_in_secs)
+            .await
+    }
+}
@@ -0,0 +1,292 @@
+use serde::Serialize;
+
+use crate::domain::{Note, NoteId, UserProfile};
+use crate::errors::ApiError;
+use crate::related::{fetch_chain, NoteChain};
+use crate::state::AppState;
+use crate::urls::base32::{decode_id, encode_id};
+
+const MAX_LINEAGE_DEPTH: i32 = 100;
+// Upper bound on the LCS table; larger changes fall back to replacing the differing block.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Serialize, Clone)]
+pub struct Revision {
+    /// 1 for the original post, counting up to the latest version.
+    pub number: usize,
+    pub id: String,
+    pub created_at: String,
+    pub author: UserProfile,
+    pub segments: Vec<String>,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retracted_at: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct History {
+    pub post_id: String,
+    pub revisions: Vec<Revision>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct RevisionDiff {
+    pub from: String,
+    pub to: String,
+    pub lines: Vec<DiffLine>,
+}
+
+impl History {
+    pub fn revision(&self, id: &str) -> Option<&Revision> {
+        self.revisions.iter().find(|revision| revision.id == id)
+    }
+
+    /// Diffs `from` against `to`. `to` defaults to the latest revision and `from` to the one
+    /// before it; a post that was never versioned has nothing to compare.
+    pub fn diff(
+        &self,
+        from: Option<NoteId>,
+        to: Option<NoteId>,
+    ) -> Result<Option<RevisionDiff>, ApiError<serde_json::Value>> {
+        let to = match to {
+            Some(id) => self.find(id)?,
+            None => match self.revisions.last() {
+                Some(revision) => revision,
+                None => return Ok(None),
+            },
+        };
+        let from = match from {
+            Some(id) => self.find(id)?,
+            None => match to.number.checked_sub(2).and_then(|index| self.revisions.get(index)) {
+                Some(revision) => revision,
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(RevisionDiff {
+            from: from.id.clone(),
+            to: to.id.clone(),
+            lines: line_diff(&from.value, &to.value),
+        }))
+    }
+
+    fn find(&self, id: NoteId) -> Result<&Revision, ApiError<serde_json::Value>> {
+        self.revision(&encode_id(id.to_bytes())).ok_or_else(|| {
+            ApiError::unprocessable("invalid_revision", "Note is not a revision of this post", None)
+        })
+    }
+}
+
+/// Every revision of the post containing `note_id`, oldest first, each assembled from its chain.
+pub async fn fetch_history(
+    state: &AppState,
+    note_id: NoteId,
+) -> Result<History, ApiError<serde_json::Value>> {
+    let chain = fetch_chain(state, note_id).await?;
+    let post_id = chain.head().id.clone();
+    let head_id = decode_id(&post_id)
+        .map(NoteId::from_bytes)
+        .ok_or_else(ApiError::internal)?;
+    let lineage = state
+        .storage
+        .find_version_lineage(head_id, MAX_LINEAGE_DEPTH)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let mut revisions = Vec::with_capacity(lineage.len());
+    for (index, note) in lineage.iter().enumerate() {
+        let revision_chain = if note.id == post_id {
+            chain.clone()
+        } else {
+            let id = decode_id(&note.id)
+                .map(NoteId::from_bytes)
+                .ok_or_else(ApiError::internal)?;
+            fetch_chain(state, id).await?
+        };
+        revisions.push(revision(index + 1, &revision_chain));
+    }
+    Ok(History { post_id, revisions })
+}
+
+/// The head of the newest version of `head`'s post, or `None` when `head` is already the latest.
+pub async fn find_newer_revision(
+    state: &AppState,
+    head: &Note,
+) -> Result<Option<Note>, ApiError<serde_json::Value>> {
+    let head_id = decode_id(&head.id)
+        .map(NoteId::from_bytes)
+        .ok_or_else(ApiError::internal)?;
+    let latest = state
+        .storage
+        .find_latest_version(head_id, MAX_LINEAGE_DEPTH)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    Ok(latest.filter(|latest| latest.id != head.id))
+}
+
+fn revision(number: usize, chain: &NoteChain) -> Revision {
+    let head = chain.head();
+    let segments = chain
+        .prev
+        .iter()
+        .chain(std::iter::once(&chain.center))
+        .chain(&chain.next)
+        .map(|note| note.id.clone())
+        .collect();
+    Revision {
+        number,
+        id: head.id.clone(),
+        created_at: head.created_at.clone(),
+        author: head.author.clone(),
+        segments,
+        value: if head.is_retracted() { String::new() } else { chain.value() },
+        retracted_at: head.retracted_at.clone(),
+    }
+}
+
+/// Line diff via the longest common subsequence, after trimming the shared prefix and suffix.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(|text| line(DiffOp::Equal, text)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) <= MAX_DIFF_CELLS {
+        lcs_diff(old_mid, new_mid, &mut lines);
+    } else {
+        lines.extend(old_mid.iter().map(|text| line(DiffOp::Delete, text)));
+        lines.extend(new_mid.iter().map(|text| line(DiffOp::Insert, text)));
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|text| line(DiffOp::Equal, text)));
+    lines
+}
+
+fn lcs_diff(old: &[&str], new: &[&str], lines: &mut Vec<DiffLine>) {
+    let width = new.len() + 1;
+    // table[i * width + j] is the LCS length of old[i..] and new[j..].
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for (i, old_line) in old.iter().enumerate().rev() {
+        for (j, new_line) in new.iter().enumerate().rev() {
+            table[i * width + j] = if old_line == new_line {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(line(DiffOp::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            lines.push(line(DiffOp::Delete, old[i]));
+            i += 1;
+        } else {
+            lines.push(line(DiffOp::Insert, new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|text| line(DiffOp::Delete, text)));
+    lines.extend(new[j..].iter().map(|text| line(DiffOp::Insert, text)));
+}
+
+fn line(op: DiffOp, text: &str) -> DiffLine {
+    DiffLine {
+        op,
+        text: text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{line_diff, DiffLine, DiffOp, MAX_DIFF_CELLS};
+
+    fn ops(lines: &[DiffLine]) -> Vec<(DiffOp, &str)> {
+        lines.iter().map(|line| (line.op, line.text.as_str())).collect()
+    }
+
+    #[test]
+    fn keeps_the_shared_prefix_and_suffix() {
+        let diff = line_diff("a\nb\nc\nd", "a\nx\nc\nd");
+        assert_eq!(
+            ops(&diff),
+            [
+                (DiffOp::Equal, "a"),
+                (DiffOp::Delete, "b"),
+                (DiffOp::Insert, "x"),
+                (DiffOp::Equal, "c"),
+                (DiffOp::Equal, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn inserts_only() {
+        let diff = line_diff("a\nc", "a\nb\nc\nd");
+        assert_eq!(
+            ops(&diff),
+            [
+                (DiffOp::Equal, "a"),
+                (DiffOp::Insert, "b"),
+                (DiffOp::Equal, "c"),
+                (DiffOp::Insert, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn deletes_only() {
+        let diff = line_diff("a\nb\nc\nd", "b\nd");
+        assert_eq!(
+            ops(&diff),
+            [
+                (DiffOp::Delete, "a"),
+                (DiffOp::Equal, "b"),
+                (DiffOp::Delete, "c"),
+                (DiffOp::Equal, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_delete_then_insert_past_the_cell_limit() {
+        // Two middles of n distinct lines around one shared line, so n * n exceeds the limit.
+        let n = 2001;
+        assert!(n * n > MAX_DIFF_CELLS);
+        let text = |prefix: &str| {
+            let mut lines: Vec<String> = (0..n - 1).map(|i| format!("{prefix}{i}")).collect();
+            lines.insert(n / 2, "shared".to_string());
+            format!("top\n{}\nbottom", lines.join("\n"))
+        };
+        let diff = line_diff(&text("old"), &text("new"));
+
+        assert_eq!(diff.len(), 2 * n + 2);
+        assert_eq!(ops(&diff[..1]), [(DiffOp::Equal, "top")]);
+        assert!(diff[1..=n].iter().all(|line| line.op == DiffOp::Delete));
+        assert!(diff[n + 1..=2 * n].iter().all(|line| line.op == DiffOp::Insert));
+        assert_eq!(diff[n / 2 + 1].text, "shared");
+        assert_eq!(ops(&diff[2 * n + 1..]), [(DiffOp::Equal, "bottom")]);
+    }
+}
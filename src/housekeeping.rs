@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::storage::Storage;
+
+/// Periodically purges expired sessions and other transient rows until `shutdown` flips.
+pub fn spawn_sweeper(
+    storage: Storage,
+    interval_secs: u64,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            match storage.purge_expired().await {
+                Ok(counts) if counts.total() > 0 => log::info!(
+                    "sweeper removed {} expired sessions, {} refresh tokens, {} api tokens, {} webhook deliveries and {} federation deliveries",
+                    counts.sessions,
+                    counts.refresh_tokens,
+                    counts.api_tokens,
+                    counts.webhook_deliveries,
+                    counts.federation_deliveries
+                ),
+                Ok(_) => {}
+                Err(err) => log::error!("sweeper failed: {err}"),
+            }
+        }
+        log::info!("sweeper stopped");
+    })
+}
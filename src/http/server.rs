@@ -1,12 +1,24 @@
 use actix_files::Files;
 use actix_web::middleware::{DefaultHeaders, Logger};
-use actix_web::{web, App, HttpServer};
+use actix_web::{guard, web, App, HttpServer};
+use tokio::signal::ctrl_c;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
 
-use crate::api::{account, associations, auth, feed, follows, health, notes, related, users};
+use crate::api::{
+    account, activitypub, api_tokens, associations, auth, feed, follows, health, history, identities,
+    notes, notifications, related, retractions, search, sessions, stream, users, webhooks,
+};
+use crate::auth::provider::IdentityProviders;
 use crate::config::Config;
+use crate::federation::outbox::spawn_federation_dispatcher;
+use crate::federation::Federation;
+use crate::housekeeping::spawn_sweeper;
+use crate::realtime::{spawn_listener, Realtime};
 use crate::state::AppState;
 use crate::storage::Storage;
 use crate::web as web_views;
+use crate::webhooks::spawn_webhook_dispatcher;
 
 const MAX_BODY_BYTES: usize = 1024 * 1024;
 const CACHE_STATIC: &str = "public, max-age=31536000, immutable";
@@ -14,16 +26,45 @@ const CACHE_STATIC: &str = "public, max-age=31536000, immutable";
 pub async fn run_server(
     config: Config,
     storage: Storage,
+    providers: IdentityProviders,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let bind_addr = config.bind_addr.clone();
-    let state = AppState { config, storage };
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let sweeper = spawn_sweeper(storage.clone(), config.sweep_interval_secs, shutdown_rx.clone());
+    let dispatcher = spawn_webhook_dispatcher(
+        storage.clone(),
+        config.webhook_poll_interval_secs,
+        config.webhook_allow_insecure,
+        shutdown_rx.clone(),
+    );
+    let federation = Federation::from_config(&config);
+    let outbox = spawn_federation_dispatcher(
+        storage.clone(),
+        federation.clone(),
+        config.public_base_url.clone(),
+        config.activitypub_poll_interval_secs,
+        shutdown_rx.clone(),
+    );
+    let realtime = Realtime::new();
+    let listener = spawn_listener(config.database_url.clone(), realtime.clone(), shutdown_rx);
+    let state = AppState {
+        config,
+        storage,
+        providers,
+        federation,
+        realtime,
+    };
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         let payload_config = web::PayloadConfig::new(MAX_BODY_BYTES);
         App::new()
             .app_data(web::Data::new(state.clone()))
             .app_data(payload_config)
-            .wrap(Logger::default())
+            .wrap(
+                // Paths only: feed URLs may carry a personal access token in the query string.
+                Logger::new(r#"%a "%{method}xi %U" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+                    .custom_request_replace("method", |req| req.method().to_string()),
+            )
             .service(web::resource("/").route(web::get().to(web_views::home)))
             .service(web::resource("/signin").route(web::get().to(web_views::signin)))
             .service(web::resource("/terms").route(web::get().to(web_views::terms)))
@@ -38,8 +79,56 @@ pub async fn run_server(
                 web::resource("/auth/google/redirect")
                     .route(web::post().to(auth::post_google_redirect)),
             )
+            .service(web::resource("/auth/oidc/{provider}").route(web::post().to(auth::post_oidc)))
             .service(web::resource("/auth/me").route(web::get().to(auth::get_me)))
+            .service(
+                web::resource("/auth/identities")
+                    .route(web::get().to(identities::get_identities))
+                    .route(web::post().to(identities::post_identities)),
+            )
+            .service(
+                web::resource("/auth/identities/{provider}")
+                    .route(web::delete().to(identities::delete_identity)),
+            )
+            .service(web::resource("/auth/logout").route(web::post().to(sessions::post_logout)))
+            .service(web::resource("/auth/refresh").route(web::post().to(sessions::post_refresh)))
+            .service(
+                web::resource("/auth/sessions")
+                    .route(web::get().to(sessions::get_sessions))
+                    .route(web::delete().to(sessions::delete_other_sessions)),
+            )
+            .service(
+                web::resource("/auth/sessions/{id}").route(web::delete().to(sessions::delete_session)),
+            )
+            .service(
+                web::resource("/account")
+                    .route(web::patch().to(account::patch_account))
+                    .route(web::delete().to(account::delete_account)),
+            )
             .service(web::resource("/account/note").route(web::post().to(account::post_account_note)))
+            .service(web::resource("/account/export").route(web::get().to(account::get_account_export)))
+            .service(
+                web::resource("/account/tokens")
+                    .route(web::get().to(api_tokens::get_api_tokens))
+                    .route(web::post().to(api_tokens::post_api_tokens)),
+            )
+            .service(
+                web::resource("/account/tokens/{id}")
+                    .route(web::delete().to(api_tokens::delete_api_token)),
+            )
+            .service(
+                web::resource("/account/webhooks")
+                    .route(web::get().to(webhooks::get_webhooks))
+                    .route(web::post().to(webhooks::post_webhooks)),
+            )
+            .service(
+                web::resource("/account/webhooks/{id}")
+                    .route(web::delete().to(webhooks::delete_webhook)),
+            )
+            .service(
+                web::resource("/account/webhooks/{id}/deliveries")
+                    .route(web::get().to(webhooks::get_webhook_deliveries)),
+            )
             .service(
                 web::resource("/notes")
                     .route(web::post().to(notes::post_notes))
@@ -48,20 +137,84 @@ pub async fn run_server(
             .service(web::resource("/notes/{id}/versions").route(web::post().to(notes::post_note_version)))
             .service(web::resource("/notes/random").route(web::get().to(notes::get_random_notes)))
             .service(web::resource("/feed").route(web::get().to(feed::get_feed)))
+            .service(web::resource("/feed/stream").route(web::get().to(stream::get_feed_stream)))
+            .service(web::resource("/search").route(web::get().to(search::get_search)))
+            .service(
+                web::resource("/notifications").route(web::get().to(notifications::get_notifications)),
+            )
+            .service(
+                web::resource("/notifications/read")
+                    .route(web::post().to(notifications::post_notifications_read)),
+            )
             .service(
                 web::resource("/associations")
                     .route(web::post().to(associations::post_associations))
+                    .route(web::delete().to(associations::delete_associations))
                     .route(web::get().to(associations::get_associations)),
             )
+            .service(
+                web::resource("/associations/removals")
+                    .route(web::get().to(associations::get_association_removals)),
+            )
             .service(
                 web::resource("/follows")
                     .route(web::post().to(follows::post_follows))
                     .route(web::delete().to(follows::delete_follows))
                     .route(web::get().to(follows::get_follows)),
             )
+            .service(web::resource("/follows/check").route(web::get().to(follows::get_follow_check)))
             .service(web::resource("/notes/{id}/related").route(web::get().to(related::get_related)))
-            .service(web::resource("/notes/{id}").route(web::get().to(notes::get_note_by_id)))
-            .service(web::resource("/users/{id}").route(web::get().to(users::get_user_by_id)))
+            .service(web::resource("/notes/{id}/thread").route(web::get().to(related::get_thread)))
+            .service(web::resource("/notes/{id}/stream").route(web::get().to(stream::get_note_stream)))
+            .service(web::resource("/notes/{id}/history").route(web::get().to(history::get_note_history)))
+            .service(web::resource("/notes/{id}/latest").route(web::get().to(history::get_latest_version)))
+            .service(
+                web::resource("/notes/{id}")
+                    .route(
+                        web::get()
+                            .guard(guard::fn_guard(activitypub::accepts_activity))
+                            .to(activitypub::get_note_object),
+                    )
+                    .route(web::get().to(notes::get_note_by_id))
+                    .route(web::delete().to(retractions::delete_note)),
+            )
+            .service(
+                web::resource("/.well-known/webfinger")
+                    .route(web::get().to(activitypub::get_webfinger)),
+            )
+            .service(
+                web::resource("/users/{id}")
+                    .route(
+                        web::get()
+                            .guard(guard::fn_guard(activitypub::accepts_activity))
+                            .to(activitypub::get_actor),
+                    )
+                    .route(
+                        web::get()
+                            .guard(guard::fn_guard(web_views::accepts_html))
+                            .to(web_views::user_page),
+                    )
+                    .route(web::get().to(users::get_user_by_id)),
+            )
+            .service(web::resource("/feed.atom").route(web::get().to(web_views::personal_feed_atom)))
+            .service(web::resource("/feed.json").route(web::get().to(web_views::personal_feed_json)))
+            .service(
+                web::resource("/users/{id}/feed.atom").route(web::get().to(web_views::user_feed_atom)),
+            )
+            .service(
+                web::resource("/users/{id}/feed.json").route(web::get().to(web_views::user_feed_json)),
+            )
+            .service(web::resource("/users/{id}/inbox").route(web::post().to(activitypub::post_inbox)))
+            .service(web::resource("/users/{id}/outbox").route(web::get().to(activitypub::get_outbox)))
+            .service(
+                web::resource("/users/{id}/followers").route(web::get().to(activitypub::get_followers)),
+            )
+            .service(
+                web::resource("/users/{id}/account-notes")
+                    .route(web::get().to(users::get_account_note_history)),
+            )
+            .service(web::resource("/@{handle}").route(web::get().to(web_views::profile_page)))
+            .service(web::resource("/{id}/history").route(web::get().to(web_views::history_page)))
             .service(web::resource("/{id}").route(web::get().to(web_views::note_page)))
             .service(
                 web::scope("/assets")
@@ -70,7 +223,40 @@ pub async fn run_server(
             )
     })
     .bind(bind_addr)?
-    .run()
-    .await?;
+    .disable_signals()
+    .run();
+
+    // Background tasks stop before the graceful stop begins: the listener then closes open event
+    // streams, which would otherwise hold the server until its shutdown timeout.
+    let handle = server.handle();
+    let stopper = tokio::spawn(async move {
+        shutdown_signal().await;
+        shutdown_tx.send(true).ok();
+        handle.stop(true).await;
+    });
+    let result = server.await;
+    // Aborting drops the sender if no signal arrived, which stops the background tasks as well.
+    stopper.abort();
+    sweeper.await.ok();
+    dispatcher.await.ok();
+    outbox.await.ok();
+    listener.await.ok();
+    result?;
     Ok(())
 }
+
+async fn shutdown_signal() {
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(err) => {
+            log::error!("cannot listen for SIGTERM: {err}");
+            ctrl_c().await.ok();
+            return;
+        }
+    };
+    tokio::select! {
+        _ = ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+    log::info!("shutdown signal received");
+}
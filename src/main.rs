@@ -1,21 +1,32 @@
 mod api;
 mod auth;
 mod config;
+mod dispatch;
 mod domain;
 mod errors;
+mod federation;
+mod history;
+mod housekeeping;
 mod http;
+mod outbound;
+mod realtime;
 mod related;
 mod state;
 mod storage;
+#[cfg(test)]
+mod testing;
 mod urls;
 mod web;
+mod webhooks;
 
+use crate::auth::provider::IdentityProviders;
 use crate::config::Config;
 use crate::http::server::run_server;
 use crate::storage::Storage;
 
 #[actix_web::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
     let config = Config::from_env()?;
     let storage = Storage::connect(&config).await?;
 
@@ -23,5 +34,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         storage.run_migrations(&config.migrations_path).await?;
     }
 
-    run_server(config, storage).await
+    let providers = IdentityProviders::from_config(&config).await;
+    run_server(config, storage, providers).await
 }
@@ -0,0 +1,141 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::ClientBuilder;
+use url::{Host, Url};
+
+/// Resolves names for requests to user-supplied URLs, keeping only public addresses. It runs when
+/// the connection is made, so a name that re-resolves to an internal address after a check is
+/// still refused.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Applies the public-address guard to a client unless private peers are allowed for local testing.
+pub fn guard(builder: ClientBuilder, allow_private: bool) -> ClientBuilder {
+    if allow_private {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicResolver))
+    }
+}
+
+/// Rejects a URL whose host is a literal non-public IP; literals never reach the resolver.
+pub fn check_literal_host(url: &Url) -> Result<(), String> {
+    let ip = match url.host() {
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        Some(Host::Domain(_)) => return Ok(()),
+        None => return Err("URL has no host".to_string()),
+    };
+    if is_public_ip(ip) {
+        Ok(())
+    } else {
+        Err(format!("{ip} is not a public address"))
+    }
+}
+
+/// Resolves a URL's host now and requires every address to be public. Used when a URL is stored,
+/// so mistakes surface up front; requests are guarded again at connect time.
+pub async fn check_public_host(url: &Url) -> Result<(), String> {
+    check_literal_host(url)?;
+    let Some(Host::Domain(domain)) = url.host() else {
+        return Ok(());
+    };
+    let port = url.port_or_known_default().unwrap_or(443);
+    let mut addrs = tokio::net::lookup_host((domain, port))
+        .await
+        .map_err(|_| format!("{domain} does not resolve"))?
+        .peekable();
+    if addrs.peek().is_none() {
+        return Err(format!("{domain} does not resolve"));
+    }
+    if let Some(addr) = addrs.find(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!("{domain} resolves to {}, which is not public", addr.ip()));
+    }
+    Ok(())
+}
+
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(mapped);
+            }
+            let segments = ip.segments();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10.
+                || (segments[0] & 0xfe00) == 0xfc00
+                || (segments[0] & 0xffc0) == 0xfe80
+                // Documentation 2001:db8::/32 and NAT64 64:ff9b::/96, which reaches IPv4 hosts.
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+                || (segments[0] == 0x0064 && segments[1] == 0xff9b))
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space 100.64.0.0/10, benchmarking 198.18.0.0/15 and reserved 240.0.0.0/4.
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 198 && (b & 0xfe) == 18)
+        || a >= 240)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::is_public_ip;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse::<IpAddr>().unwrap())
+    }
+
+    #[test]
+    fn rejects_private_and_reserved_ipv4() {
+        for ip in ["10.0.0.1", "100.64.0.1", "198.18.0.1", "240.0.0.1"] {
+            assert!(!public(ip), "{ip} should not be public");
+        }
+    }
+
+    #[test]
+    fn rejects_non_global_ipv6() {
+        // Mapped loopback, NAT64, link-local and unique local.
+        for ip in ["::ffff:127.0.0.1", "64:ff9b::1", "fe80::1", "fc00::1"] {
+            assert!(!public(ip), "{ip} should not be public");
+        }
+    }
+
+    #[test]
+    fn accepts_public_addresses() {
+        assert!(public("93.184.216.34"));
+        assert!(public("2606:4700:4700::1111"));
+    }
+}
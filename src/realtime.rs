@@ -0,0 +1,110 @@
+use std::future::poll_fn;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_postgres::{AsyncMessage, NoTls};
+
+use crate::domain::StreamEvent;
+use crate::storage::{StorageError, EVENTS_CHANNEL};
+
+const HUB_CAPACITY: usize = 1024;
+const MAX_RECONNECT_SECS: u64 = 30;
+
+/// In-process fan-out of change notices received over LISTEN, one receiver per open stream.
+#[derive(Clone)]
+pub struct Realtime {
+    sender: Arc<RwLock<broadcast::Sender<StreamEvent>>>,
+}
+
+impl Realtime {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(HUB_CAPACITY);
+        Self {
+            sender: Arc::new(RwLock::new(sender)),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamEvent> {
+        self.sender.read().unwrap_or_else(|err| err.into_inner()).subscribe()
+    }
+
+    fn publish(&self, event: StreamEvent) {
+        // Sending only fails when no stream is open, which is fine.
+        self.sender.read().unwrap_or_else(|err| err.into_inner()).send(event).ok();
+    }
+
+    /// Closes every open subscription. Notices sent while the listener was disconnected are
+    /// lost, so streams end and clients reconnect and reload rather than miss them silently.
+    fn reset(&self) {
+        let (sender, _) = broadcast::channel(HUB_CAPACITY);
+        *self.sender.write().unwrap_or_else(|err| err.into_inner()) = sender;
+    }
+}
+
+/// Holds a dedicated connection LISTENing on the events channel and republishes every notice to
+/// `realtime`, reconnecting with backoff until `shutdown` flips. Each app instance runs its own
+/// listener, so writes on any instance reach streams on all of them.
+pub fn spawn_listener(
+    database_url: String,
+    realtime: Realtime,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = 1;
+        loop {
+            tokio::select! {
+                result = listen(&database_url, &realtime) => match result {
+                    Ok(()) => {
+                        log::warn!("event listener connection closed");
+                        backoff = 1;
+                    }
+                    Err(err) => log::error!("event listener failed: {err}"),
+                },
+                _ = shutdown.changed() => break,
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(backoff)) => {}
+                _ = shutdown.changed() => break,
+            }
+            backoff = (backoff * 2).min(MAX_RECONNECT_SECS);
+        }
+        // Ends open streams so they do not hold up the server's graceful stop.
+        realtime.reset();
+        log::info!("event listener stopped");
+    })
+}
+
+async fn listen(database_url: &str, realtime: &Realtime) -> Result<(), StorageError> {
+    let (client, mut connection) = tokio_postgres::connect(database_url, NoTls).await?;
+    // Notifications arrive through the connection future, which must be polled for LISTEN to run.
+    let (notices, mut received) = mpsc::unbounded_channel();
+    let driver = tokio::spawn(async move {
+        while let Some(message) = poll_fn(|cx| connection.poll_message(cx)).await {
+            match message {
+                Ok(AsyncMessage::Notification(notification)) => {
+                    if notices.send(notification.payload().to_string()).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    log::error!("event listener connection error: {err}");
+                    break;
+                }
+            }
+        }
+    });
+    client.batch_execute(&format!("LISTEN {EVENTS_CHANNEL}")).await?;
+    realtime.reset();
+    log::info!("listening for live updates on {EVENTS_CHANNEL}");
+    while let Some(payload) = received.recv().await {
+        match serde_json::from_str::<StreamEvent>(&payload) {
+            Ok(event) => realtime.publish(event),
+            Err(err) => log::warn!("ignoring malformed event notice: {err}"),
+        }
+    }
+    driver.abort();
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
-use crate::domain::{Association, Note, NoteId};
+use crate::domain::{Association, Note, NoteId, ThreadEdge};
 use crate::errors::ApiError;
 use crate::state::AppState;
 use crate::urls::base32::decode_id;
@@ -15,12 +15,47 @@ pub struct RelatedResponse {
     pub center: Note,
     pub related: Vec<RelatedEntry>,
 }
+#[derive(Serialize, Clone)]
+pub struct ThreadNode {
+    pub kind: String,
+    pub note: Note,
+    /// Every reply and quote to this note, including those past the depth or breadth limit.
+    pub reply_count: i64,
+    pub replies: Vec<ThreadNode>,
+}
+#[derive(Serialize, Clone)]
+pub struct ThreadResponse {
+    pub center: Note,
+    pub reply_count: i64,
+    /// What the center replies to or quotes, from the top of the conversation down.
+    pub ancestors: Vec<RelatedEntry>,
+    pub replies: Vec<ThreadNode>,
+}
+const MAX_THREAD_NODES: i64 = 500;
+const MAX_THREAD_ANCESTORS: i32 = 20;
 #[derive(Clone)]
 pub struct NoteChain {
     pub center: Note,
     pub prev: Vec<Note>,
     pub next: Vec<Note>,
 }
+impl NoteChain {
+    /// The first note of the post, which versions and the note page are keyed on.
+    pub fn head(&self) -> &Note {
+        self.prev.first().unwrap_or(&self.center)
+    }
+    /// The whole post, segments joined in chain order.
+    pub fn value(&self) -> String {
+        let parts: Vec<&str> = self
+            .prev
+            .iter()
+            .chain(std::iter::once(&self.center))
+            .chain(&self.next)
+            .map(|note| note.value.as_str())
+            .collect();
+        parts.join("\n\n")
+    }
+}
 pub async fn fetch_related(
     state: &AppState,
     note_id: NoteId,
@@ -89,89 +124,94 @@ pub async fn fetch_chain(
     state: &AppState,
     note_id: NoteId,
 ) -> Result<NoteChain, ApiError<serde_json::Value>> {
-    let center = state
+    let (center, mut prev, next) = state
         .storage
-        .find_note(note_id)
+        .find_note_chain(note_id)
         .await
         .map_err(|_| ApiError::internal())?
         .ok_or_else(|| ApiError::not_found("note_not_found", "Note not found"))?;
-    let prev_ids = walk_chain_ids(state, &center.id, Direction::Prev).await?;
-    let next_ids = walk_chain_ids(state, &center.id, Direction::Next).await?;
-    let mut lookup_ids = Vec::new();
-    let mut unique = HashSet::new();
-    for id in prev_ids.iter().chain(next_ids.iter()) {
-        if unique.insert(id.clone()) {
-            if let Some(bytes) = decode_id(id) {
-                lookup_ids.push(NoteId::from_bytes(bytes));
-            }
-        }
-    }
-    let notes = if lookup_ids.is_empty() {
-        Vec::new()
-    } else {
-        state
-            .storage
-            .find_notes_by_ids(&lookup_ids)
-            .await
-            .map_err(|_| ApiError::internal())?
-    };
-    let note_map: HashMap<String, Note> =
-        notes.into_iter().map(|note| (note.id.clone(), note)).collect();
-    let center_author = center.author.user_id;
-    let mut prev = collect_chain_notes(&prev_ids, &note_map, center_author);
     prev.reverse();
-    let next = collect_chain_notes(&next_ids, &note_map, center_author);
     Ok(NoteChain { center, prev, next })
 }
-#[derive(Clone, Copy)]
-enum Direction {
-    Prev,
-    Next,
-}
-async fn walk_chain_ids(
+/// The conversation around a note: its reply/quote ancestors and a tree of replies and quotes
+/// up to `depth` levels deep with at most `breadth` children per note.
+pub async fn fetch_thread(
     state: &AppState,
-    start_id: &str,
-    direction: Direction,
-) -> Result<Vec<String>, ApiError<serde_json::Value>> {
-    let mut chain = Vec::new();
-    let mut seen = HashSet::new();
-    let mut current_id = start_id.to_string();
-    seen.insert(current_id.clone());
-    loop {
-        let Some(bytes) = decode_id(&current_id) else { break };
-        let associations = state
-            .storage
-            .list_associations(NoteId::from_bytes(bytes))
-            .await
-            .map_err(|_| ApiError::internal())?;
-        let next_id = associations
-            .iter()
-            .find_map(|assoc| match direction {
-                Direction::Prev => resolve_prev_id(assoc, &current_id),
-                Direction::Next => resolve_next_id(assoc, &current_id),
-            });
-        let Some(next_id) = next_id else { break };
-        if !seen.insert(next_id.to_string()) {
+    note_id: NoteId,
+    depth: usize,
+    breadth: usize,
+) -> Result<ThreadResponse, ApiError<serde_json::Value>> {
+    let (center, reply_count, edges) = state
+        .storage
+        .find_thread_descendants(note_id, depth as i32, breadth as i64, MAX_THREAD_NODES)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("note_not_found", "Note not found"))?;
+    let ancestors = state
+        .storage
+        .find_thread_ancestors(note_id, MAX_THREAD_ANCESTORS)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    // Walk down from the center so the context stops at the first hidden step.
+    let mut child_author = center.author.user_id;
+    let mut context = Vec::new();
+    for edge in ancestors.into_iter().rev() {
+        if !association_visible(&edge.association, edge.note.author.user_id, child_author) {
             break;
         }
-        chain.push(next_id.to_string());
-        current_id = next_id.to_string();
+        child_author = edge.note.author.user_id;
+        context.push(RelatedEntry {
+            association: edge.association,
+            note: edge.note,
+        });
     }
-    Ok(chain)
-}
-fn resolve_prev_id<'a>(assoc: &'a Association, current_id: &str) -> Option<&'a str> {
-    match assoc.kind.as_str() {
-        "prev" if assoc.from_id == current_id => Some(assoc.to_id.as_str()),
-        "next" if assoc.to_id == current_id => Some(assoc.from_id.as_str()),
-        _ => None,
+    context.reverse();
+    // A note reached along several paths is walked once per path; keep one copy of its children.
+    let mut children: HashMap<String, Vec<ThreadEdge>> = HashMap::new();
+    for edge in edges {
+        let siblings = children.entry(edge.association.to_id.clone()).or_default();
+        let duplicate = siblings.iter().any(|sibling| {
+            sibling.note.id == edge.note.id && sibling.association.kind == edge.association.kind
+        });
+        if !duplicate {
+            siblings.push(edge);
+        }
     }
+    let mut path = vec![center.id.clone()];
+    let replies = build_thread(&children, &center, &mut path);
+    Ok(ThreadResponse {
+        center,
+        reply_count,
+        ancestors: context,
+        replies,
+    })
 }
-fn resolve_next_id<'a>(assoc: &'a Association, current_id: &str) -> Option<&'a str> {
-    match assoc.kind.as_str() {
-        "next" if assoc.from_id == current_id => Some(assoc.to_id.as_str()),
-        "prev" if assoc.to_id == current_id => Some(assoc.from_id.as_str()),
-        _ => None,
+fn build_thread(
+    children: &HashMap<String, Vec<ThreadEdge>>,
+    parent: &Note,
+    path: &mut Vec<String>,
+) -> Vec<ThreadNode> {
+    let Some(edges) = children.get(&parent.id) else {
+        return Vec::new();
+    };
+    let mut nodes = Vec::new();
+    for edge in edges {
+        if path.contains(&edge.note.id)
+            || !association_visible(&edge.association, parent.author.user_id, edge.note.author.user_id)
+        {
+            continue;
+        }
+        path.push(edge.note.id.clone());
+        let replies = build_thread(children, &edge.note, path);
+        path.pop();
+        nodes.push(ThreadNode {
+            kind: edge.association.kind.clone(),
+            note: edge.note.clone(),
+            reply_count: edge.reply_count,
+            replies,
+        });
     }
+    nodes
 }
 fn allows_cross_author(kind: &str) -> bool {
     matches!(kind, "link" | "reply" | "quote")
@@ -182,18 +222,3 @@ fn association_visible(association: &Association, center_author: Uuid, other_aut
     }
     true
 }
-fn collect_chain_notes(
-    ids: &[String],
-    note_map: &HashMap<String, Note>,
-    author_id: Uuid,
-) -> Vec<Note> {
-    let mut notes = Vec::new();
-    for id in ids {
-        let Some(note) = note_map.get(id) else { break };
-        if note.author.user_id != author_id {
-            break;
-        }
-        notes.push(note.clone());
-    }
-    notes
-}
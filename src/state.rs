@@ -1,8 +1,14 @@
+use crate::auth::provider::IdentityProviders;
 use crate::config::Config;
+use crate::federation::Federation;
+use crate::realtime::Realtime;
 use crate::storage::Storage;
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Config,
     pub storage: Storage,
+    pub providers: IdentityProviders,
+    pub federation: Federation,
+    pub realtime: Realtime,
 }
@@ -0,0 +1,206 @@
+use tokio::sync::mpsc;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::{Client, IsolationLevel, Row};
+use uuid::Uuid;
+
+use crate::domain::note::format_timestamp;
+use crate::domain::{default_handle, ExportRecord, RemoteFollower, User};
+use crate::storage::associations::map_association;
+use crate::storage::follows::map_edge;
+use crate::storage::identities::map_identity;
+use crate::storage::notes::map_note;
+use crate::storage::users::map_user;
+use crate::storage::StorageError;
+
+const EXPORT_BATCH: i32 = 500;
+
+/// A query over `$1 = user_id` and how each of its rows becomes an export record.
+type ExportSection = (&'static str, fn(&Row) -> ExportRecord);
+
+/// Streams everything stored about a user, including retracted notes, for an access request.
+/// Notes, associations and follows are read through cursors in batches inside one read-only
+/// snapshot, so memory stays flat however large the account. Stops early once `records` closes.
+pub async fn export_account(
+    client: &mut Client,
+    user: &User,
+    records: &mpsc::Sender<ExportRecord>,
+) -> Result<(), StorageError> {
+    let transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .await?;
+    let row = transaction
+        .query_one(
+            "SELECT created_at FROM users WHERE user_id = $1",
+            &[&user.user_id],
+        )
+        .await?;
+    let created_at: time::OffsetDateTime = row.get(0);
+    let profile = ExportRecord::Profile {
+        profile: user.account_profile(),
+        created_at: format_timestamp(created_at),
+    };
+    if records.send(profile).await.is_err() {
+        return Ok(());
+    }
+
+    let sections: [ExportSection; 7] = [
+        (
+            "SELECT provider, subject, email, created_at FROM user_identities \
+             WHERE user_id = $1 ORDER BY created_at, provider",
+            |row| ExportRecord::Identity(map_identity(row)),
+        ),
+        (
+            "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at \
+             FROM users u JOIN notes n ON n.id = u.account_note_id \
+             WHERE u.user_id = $1",
+            |row| ExportRecord::AccountNote(map_note(row)),
+        ),
+        (
+            "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at \
+             FROM notes n JOIN users u ON u.user_id = n.author_id \
+             WHERE n.author_id = $1 \
+             ORDER BY n.created_at, n.id",
+            |row| ExportRecord::Note(map_note(row)),
+        ),
+        (
+            "SELECT a.kind, a.from_id, a.to_id, a.created_at FROM associations a \
+             WHERE EXISTS (SELECT 1 FROM notes n WHERE n.author_id = $1 AND (n.id = a.from_id OR n.id = a.to_id)) \
+             ORDER BY a.created_at, a.id",
+            |row| ExportRecord::Association(map_association(row)),
+        ),
+        (
+            "SELECT u.user_id, u.handle, u.display_name, u.account_note_id, f.created_at \
+             FROM follows f JOIN users u ON u.user_id = f.follower_id \
+             WHERE f.followee_id = $1 \
+             ORDER BY f.created_at DESC, u.user_id DESC",
+            |row| ExportRecord::Follower(map_edge(row)),
+        ),
+        (
+            "SELECT r.actor_uri, f.created_at \
+             FROM follows f JOIN remote_actors r ON r.id = f.remote_follower_id \
+             WHERE f.followee_id = $1 \
+             ORDER BY f.created_at DESC, r.id DESC",
+            |row| {
+                ExportRecord::RemoteFollower(RemoteFollower {
+                    actor_uri: row.get(0),
+                    created_at: format_timestamp(row.get(1)),
+                })
+            },
+        ),
+        (
+            "SELECT u.user_id, u.handle, u.display_name, u.account_note_id, f.created_at \
+             FROM follows f JOIN users u ON u.user_id = f.followee_id \
+             WHERE f.follower_id = $1 \
+             ORDER BY f.created_at DESC, u.user_id DESC",
+            |row| ExportRecord::Following(map_edge(row)),
+        ),
+    ];
+    for (query, map) in sections {
+        let statement = transaction.prepare(query).await?;
+        let portal = transaction.bind(&statement, &[&user.user_id]).await?;
+        loop {
+            let rows = transaction.query_portal(&portal, EXPORT_BATCH).await?;
+            for row in &rows {
+                if records.send(map(row)).await.is_err() {
+                    return Ok(());
+                }
+            }
+            if rows.len() < EXPORT_BATCH as usize {
+                break;
+            }
+        }
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+pub enum ProfileUpdate {
+    Updated(User),
+    HandleTaken,
+}
+
+/// Sets the handle and display name; `None` leaves a field unchanged and an empty display name clears it.
+pub async fn update_profile(
+    client: &Client,
+    user_id: Uuid,
+    handle: Option<&str>,
+    display_name: Option<&str>,
+) -> Result<Option<ProfileUpdate>, StorageError> {
+    let result = client
+        .query_opt(
+            "UPDATE users SET handle = COALESCE($2, handle), \
+                 display_name = CASE WHEN $3::text IS NULL THEN display_name ELSE NULLIF($3, '') END \
+             WHERE user_id = $1 AND deleted_at IS NULL \
+             RETURNING user_id, email, account_note_id, handle, display_name",
+            &[&user_id, &handle, &display_name],
+        )
+        .await;
+    match result {
+        Ok(row) => Ok(row.as_ref().map(|row| ProfileUpdate::Updated(map_user(row)))),
+        Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+            Ok(Some(ProfileUpdate::HandleTaken))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Revokes sessions, unlinks identities, drops follows and notifications, retracts every note and scrubs the email and chosen handle.
+/// The user row is kept as an anonymous tombstone because notes.author_id references it.
+pub async fn delete_account(client: &mut Client, user_id: Uuid) -> Result<u64, StorageError> {
+    let transaction = client.transaction().await?;
+    transaction
+        .execute("DELETE FROM sessions WHERE user_id = $1", &[&user_id])
+        .await?;
+    transaction
+        .execute("DELETE FROM refresh_tokens WHERE user_id = $1", &[&user_id])
+        .await?;
+    transaction
+        .execute("DELETE FROM api_tokens WHERE user_id = $1", &[&user_id])
+        .await?;
+    transaction
+        .execute("DELETE FROM actor_keys WHERE user_id = $1", &[&user_id])
+        .await?;
+    transaction
+        .execute("DELETE FROM webhooks WHERE user_id = $1", &[&user_id])
+        .await?;
+    transaction
+        .execute(
+            "DELETE FROM federation_deliveries WHERE user_id = $1 AND status = 'pending'",
+            &[&user_id],
+        )
+        .await?;
+    transaction
+        .execute(
+            "DELETE FROM notifications WHERE user_id = $1 OR actor_id = $1",
+            &[&user_id],
+        )
+        .await?;
+    transaction
+        .execute("DELETE FROM user_identities WHERE user_id = $1", &[&user_id])
+        .await?;
+    transaction
+        .execute(
+            "DELETE FROM follows WHERE follower_id = $1 OR followee_id = $1",
+            &[&user_id],
+        )
+        .await?;
+    let retracted = transaction
+        .execute(
+            "UPDATE notes SET value = ''::bytea, retracted_at = NOW() \
+             WHERE author_id = $1 AND retracted_at IS NULL",
+            &[&user_id],
+        )
+        .await?;
+    transaction
+        .execute(
+            "UPDATE users SET email = '', handle = $2, display_name = NULL, deleted_at = NOW() \
+             WHERE user_id = $1",
+            &[&user_id, &default_handle(user_id)],
+        )
+        .await?;
+    transaction.commit().await?;
+    Ok(retracted)
+}
@@ -0,0 +1,86 @@
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+use crate::auth::sessions::hash_token;
+use crate::domain::note::format_timestamp;
+use crate::domain::{ApiToken, User};
+use crate::storage::users::map_user;
+use crate::storage::StorageError;
+
+pub async fn create_api_token(
+    client: &Client,
+    user_id: Uuid,
+    name: &str,
+    token: &str,
+    scopes: &[String],
+    expires_at: Option<time::OffsetDateTime>,
+) -> Result<ApiToken, StorageError> {
+    let row = client
+        .query_one(
+            "INSERT INTO api_tokens (id, user_id, name, token_hash, scopes, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, NOW()) \
+             RETURNING id, name, scopes, created_at, expires_at, last_used_at",
+            &[&Uuid::new_v4(), &user_id, &name, &hash_token(token), &scopes, &expires_at],
+        )
+        .await?;
+    Ok(map_api_token(&row))
+}
+
+pub async fn list_api_tokens(client: &Client, user_id: Uuid) -> Result<Vec<ApiToken>, StorageError> {
+    let rows = client
+        .query(
+            "SELECT id, name, scopes, created_at, expires_at, last_used_at FROM api_tokens \
+             WHERE user_id = $1 ORDER BY created_at DESC, id",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(map_api_token).collect())
+}
+
+pub async fn delete_api_token(
+    client: &Client,
+    user_id: Uuid,
+    token_id: Uuid,
+) -> Result<bool, StorageError> {
+    let count = client
+        .execute(
+            "DELETE FROM api_tokens WHERE id = $1 AND user_id = $2",
+            &[&token_id, &user_id],
+        )
+        .await?;
+    Ok(count > 0)
+}
+
+/// Resolves an unexpired token to its owner and scopes, stamping last_used_at.
+pub async fn get_api_token_user(
+    client: &Client,
+    token: &str,
+) -> Result<Option<(User, Vec<String>)>, StorageError> {
+    let row = client
+        .query_opt(
+            "WITH t AS (\
+                 UPDATE api_tokens SET last_used_at = NOW() \
+                 WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > NOW()) \
+                 RETURNING user_id, scopes\
+             ) \
+             SELECT u.user_id, u.email, u.account_note_id, u.handle, u.display_name, t.scopes \
+             FROM t JOIN users u ON u.user_id = t.user_id \
+             WHERE u.deleted_at IS NULL",
+            &[&hash_token(token)],
+        )
+        .await?;
+    Ok(row.map(|r| (map_user(&r), r.get(5))))
+}
+
+fn map_api_token(row: &tokio_postgres::Row) -> ApiToken {
+    let expires_at: Option<time::OffsetDateTime> = row.get(4);
+    let last_used_at: Option<time::OffsetDateTime> = row.get(5);
+    ApiToken {
+        id: row.get(0),
+        name: row.get(1),
+        scopes: row.get(2),
+        created_at: format_timestamp(row.get(3)),
+        expires_at: expires_at.map(format_timestamp),
+        last_used_at: last_used_at.map(format_timestamp),
+    }
+}
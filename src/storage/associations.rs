@@ -3,7 +3,7 @@ use tokio_postgres::{Client, GenericClient};
 use tokio_postgres::error::SqlState;
 use uuid::Uuid;
 
-use crate::domain::{Association, NoteId};
+use crate::domain::{Association, AssociationRemoval, NoteId};
 use crate::domain::note::format_timestamp;
 use crate::storage::StorageError;
 use crate::urls::base32::encode_id;
@@ -11,18 +11,38 @@ use crate::urls::base32::encode_id;
 #[derive(Debug)]
 pub enum AssociationInsertError {
     VersionExists,
+    TargetUnavailable,
 }
 
 impl fmt::Display for AssociationInsertError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AssociationInsertError::VersionExists => write!(f, "version association already exists"),
+            AssociationInsertError::TargetUnavailable => write!(f, "association target is missing or retracted"),
         }
     }
 }
 
 impl std::error::Error for AssociationInsertError {}
 
+/// Fails unless the target exists and is live, and holds it until commit so a concurrent
+/// retraction waits for the association to land.
+pub async fn lock_association_target<C>(client: &C, note_id: NoteId) -> Result<(), StorageError>
+where
+    C: GenericClient + Sync,
+{
+    let row = client
+        .query_opt(
+            "SELECT 1 FROM notes WHERE id = $1 AND retracted_at IS NULL FOR SHARE",
+            &[&note_id.to_bytes().to_vec()],
+        )
+        .await?;
+    if row.is_none() {
+        return Err(Box::new(AssociationInsertError::TargetUnavailable));
+    }
+    Ok(())
+}
+
 pub async fn create_association<C>(
     client: &C,
     kind: &str,
@@ -54,17 +74,10 @@ where
             }
         }
     }
-    client
-        .execute(
-            "INSERT INTO associations (id, kind, from_id, to_id, created_at) VALUES ($1, $2, $3, $4, NOW())",
-            &[&Uuid::new_v4(), &kind, &from_vec, &to_vec],
-        )
-        .await?;
-
     let row = client
         .query_one(
-            "SELECT kind, from_id, to_id, created_at FROM associations WHERE from_id = $1 AND to_id = $2 ORDER BY created_at DESC LIMIT 1",
-            &[&from_vec, &to_vec],
+            "INSERT INTO associations (id, kind, from_id, to_id, created_at) VALUES ($1, $2, $3, $4, NOW()) RETURNING kind, from_id, to_id, created_at",
+            &[&Uuid::new_v4(), &kind, &from_vec, &to_vec],
         )
         .await?;
     Ok(map_association(&row))
@@ -84,7 +97,56 @@ pub async fn list_associations(
     Ok(rows.iter().map(map_association).collect())
 }
 
-fn map_association(row: &tokio_postgres::Row) -> Association {
+/// Deletes every matching association and records each one in `association_removals`.
+pub async fn delete_association<C>(
+    client: &C,
+    kind: &str,
+    from_id: NoteId,
+    to_id: NoteId,
+    removed_by: Uuid,
+) -> Result<Vec<AssociationRemoval>, StorageError>
+where
+    C: GenericClient + Sync,
+{
+    let rows = client
+        .query(
+            "WITH removed AS ( \
+                DELETE FROM associations WHERE kind = $1 AND from_id = $2 AND to_id = $3 \
+                RETURNING id, kind, from_id, to_id, created_at \
+             ) \
+             INSERT INTO association_removals (id, association_id, kind, from_id, to_id, created_at, removed_by, removed_at) \
+             SELECT uuid_generate_v4(), id, kind, from_id, to_id, created_at, $4, NOW() FROM removed \
+             RETURNING kind, from_id, to_id, created_at, removed_by, removed_at",
+            &[&kind, &from_id.to_bytes().to_vec(), &to_id.to_bytes().to_vec(), &removed_by],
+        )
+        .await?;
+    Ok(rows.iter().map(map_association_removal).collect())
+}
+
+pub async fn list_association_removals(
+    client: &Client,
+    note_id: NoteId,
+) -> Result<Vec<AssociationRemoval>, StorageError> {
+    let rows = client
+        .query(
+            "SELECT kind, from_id, to_id, created_at, removed_by, removed_at FROM association_removals \
+             WHERE from_id = $1 OR to_id = $1 ORDER BY removed_at DESC",
+            &[&note_id.to_bytes().to_vec()],
+        )
+        .await?;
+    Ok(rows.iter().map(map_association_removal).collect())
+}
+
+fn map_association_removal(row: &tokio_postgres::Row) -> AssociationRemoval {
+    let removed_at: time::OffsetDateTime = row.get(5);
+    AssociationRemoval {
+        association: map_association(row),
+        removed_by: row.get(4),
+        removed_at: format_timestamp(removed_at),
+    }
+}
+
+pub(crate) fn map_association(row: &tokio_postgres::Row) -> Association {
     let kind: String = row.get(0);
     let from_bytes: Vec<u8> = row.get(1);
     let to_bytes: Vec<u8> = row.get(2);
@@ -0,0 +1,169 @@
+use tokio_postgres::Client;
+
+use crate::domain::{Note, NoteId};
+use crate::storage::notes::map_note;
+use crate::storage::StorageError;
+
+// Each step follows the most recent prev/next association, like the per-hop walk it replaces.
+// The walk stops at a revisited id (cycle) or at the first note by a different author.
+const CHAIN_QUERY: &str = "WITH RECURSIVE walk AS (\
+     SELECT d.direction, c.id, c.author_id, 0 AS depth, ARRAY[c.id] AS path \
+     FROM notes c CROSS JOIN (VALUES ('prev'), ('next')) AS d(direction) \
+     WHERE c.id = $1 \
+     UNION ALL \
+     SELECT w.direction, n.id, w.author_id, w.depth + 1, w.path || n.id \
+     FROM walk w \
+     CROSS JOIN LATERAL (\
+         SELECT CASE WHEN (a.kind = 'prev') = (w.direction = 'prev') THEN a.to_id ELSE a.from_id END AS step_id \
+         FROM associations a \
+         WHERE (w.direction = 'prev' AND ((a.kind = 'prev' AND a.from_id = w.id) OR (a.kind = 'next' AND a.to_id = w.id))) \
+            OR (w.direction = 'next' AND ((a.kind = 'next' AND a.from_id = w.id) OR (a.kind = 'prev' AND a.to_id = w.id))) \
+         ORDER BY a.created_at DESC \
+         LIMIT 1\
+     ) step \
+     JOIN notes n ON n.id = step.step_id \
+     WHERE n.author_id = w.author_id AND NOT n.id = ANY(w.path)\
+ ) \
+ SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at, w.direction, w.depth \
+ FROM walk w JOIN notes n ON n.id = w.id JOIN users u ON u.user_id = n.author_id \
+ ORDER BY w.direction, w.depth";
+
+/// Loads a note with its prev and next chains, each ordered nearest first, in one round trip.
+pub async fn find_note_chain(
+    client: &Client,
+    note_id: NoteId,
+) -> Result<Option<(Note, Vec<Note>, Vec<Note>)>, StorageError> {
+    let id_bytes = note_id.to_bytes();
+    let rows = client.query(CHAIN_QUERY, &[&id_bytes.to_vec()]).await?;
+    let mut center = None;
+    let mut prev = Vec::new();
+    let mut next = Vec::new();
+    for row in &rows {
+        let direction: &str = row.get(8);
+        let depth: i32 = row.get(9);
+        match (direction, depth) {
+            (_, 0) => center = Some(map_note(row)),
+            ("prev", _) => prev.push(map_note(row)),
+            _ => next.push(map_note(row)),
+        }
+    }
+    Ok(center.map(|center| (center, prev, next)))
+}
+
+// Same walk over version associations: older steps to the note this one superseded, newer steps
+// to its most recent successor. Versions never cross authors, so neither does the walk.
+const LINEAGE_QUERY: &str = "WITH RECURSIVE walk AS (\
+     SELECT d.direction, c.id, c.author_id, 0 AS depth, ARRAY[c.id] AS path \
+     FROM notes c CROSS JOIN unnest($3::text[]) AS d(direction) \
+     WHERE c.id = $1 \
+     UNION ALL \
+     SELECT w.direction, n.id, w.author_id, w.depth + 1, w.path || n.id \
+     FROM walk w \
+     CROSS JOIN LATERAL (\
+         SELECT CASE WHEN w.direction = 'older' THEN a.from_id ELSE a.to_id END AS step_id \
+         FROM associations a \
+         WHERE a.kind = 'version' \
+           AND ((w.direction = 'older' AND a.to_id = w.id) OR (w.direction = 'newer' AND a.from_id = w.id)) \
+         ORDER BY a.created_at DESC \
+         LIMIT 1\
+     ) step \
+     JOIN notes n ON n.id = step.step_id \
+     WHERE n.author_id = w.author_id AND NOT n.id = ANY(w.path) AND w.depth < $2\
+ ) \
+ SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at, w.direction, w.depth \
+ FROM walk w JOIN notes n ON n.id = w.id JOIN users u ON u.user_id = n.author_id \
+ ORDER BY w.direction, w.depth";
+
+/// Loads every version of a post head, oldest first, going at most `max_depth` steps each way.
+pub async fn find_version_lineage(
+    client: &Client,
+    note_id: NoteId,
+    max_depth: i32,
+) -> Result<Vec<Note>, StorageError> {
+    let id_bytes = note_id.to_bytes();
+    let directions = ["older", "newer"];
+    let rows = client
+        .query(LINEAGE_QUERY, &[&id_bytes.to_vec(), &max_depth, &&directions[..]])
+        .await?;
+    let mut center = None;
+    let mut older = Vec::new();
+    let mut newer = Vec::new();
+    for row in &rows {
+        let direction: &str = row.get(8);
+        let depth: i32 = row.get(9);
+        match (direction, depth) {
+            (_, 0) => center = Some(map_note(row)),
+            ("older", _) => older.push(map_note(row)),
+            _ => newer.push(map_note(row)),
+        }
+    }
+    older.reverse();
+    Ok(older.into_iter().chain(center).chain(newer).collect())
+}
+
+/// Follows newer versions from a post head to the latest one, which is the head itself if it
+/// was never superseded.
+pub async fn find_latest_version(
+    client: &Client,
+    note_id: NoteId,
+    max_depth: i32,
+) -> Result<Option<Note>, StorageError> {
+    let id_bytes = note_id.to_bytes();
+    let directions = ["newer"];
+    let rows = client
+        .query(LINEAGE_QUERY, &[&id_bytes.to_vec(), &max_depth, &&directions[..]])
+        .await?;
+    Ok(rows.last().map(map_note))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use uuid::Uuid;
+
+    use super::find_note_chain;
+    use crate::domain::{Note, NoteId};
+    use crate::testing;
+    use crate::urls::base32::decode_id;
+
+    fn note_id(encoded: &str) -> NoteId {
+        NoteId::from_bytes(decode_id(encoded).unwrap())
+    }
+
+    fn ids(notes: &[Note]) -> Vec<&str> {
+        notes.iter().map(|note| note.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn chain_loads_in_one_query_through_a_cycle_and_a_cross_author_hop() {
+        let Some(config) = testing::config() else {
+            return;
+        };
+        let storage = testing::storage(&config).await;
+        let mut users = Vec::new();
+        for email in ["chain-a@example.com", "chain-b@example.com"] {
+            let subject = Uuid::new_v4().to_string();
+            users.push(storage.find_or_create_user("test", &subject, email).await.unwrap());
+        }
+        let segments = [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()];
+        let (_, chain) = storage
+            .create_note_chain(&segments, users[0].user_id, &[])
+            .await
+            .unwrap();
+        let (other, _) = storage
+            .create_note_chain(&[b"elsewhere".to_vec()], users[1].user_id, &[])
+            .await
+            .unwrap();
+        // three loops back to one, and one's newest prev link leaves for the other author's post.
+        storage.create_association("next", note_id(&chain[2]), note_id(&chain[0])).await.unwrap();
+        storage.create_association("prev", note_id(&chain[0]), note_id(&other.id)).await.unwrap();
+
+        let (client, executed) = testing::counting_client(&config).await;
+        let (center, prev, next) = find_note_chain(&client, note_id(&chain[1])).await.unwrap().unwrap();
+        assert_eq!(executed.load(Ordering::SeqCst), 1);
+        assert_eq!(center.id, chain[1]);
+        assert_eq!(ids(&prev), [chain[0].as_str()]);
+        assert_eq!(ids(&next), [chain[2].as_str(), chain[0].as_str()]);
+    }
+}
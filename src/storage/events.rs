@@ -0,0 +1,20 @@
+use tokio_postgres::GenericClient;
+
+use crate::domain::StreamEvent;
+use crate::storage::StorageError;
+
+/// The NOTIFY channel every instance LISTENs on for live updates.
+pub const EVENTS_CHANNEL: &str = "lgxpkf_events";
+
+/// Publishes a change notice. Inside a transaction PostgreSQL holds it until commit and drops it
+/// on rollback, so subscribers never hear about writes that did not happen.
+pub async fn notify_event<C>(client: &C, event: &StreamEvent) -> Result<(), StorageError>
+where
+    C: GenericClient + Sync,
+{
+    let payload = serde_json::to_string(event)?;
+    client
+        .execute("SELECT pg_notify($1, $2)", &[&EVENTS_CHANNEL, &payload])
+        .await?;
+    Ok(())
+}
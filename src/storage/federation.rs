@@ -0,0 +1,197 @@
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+use crate::domain::{ActorKey, RemoteActor};
+use crate::storage::StorageError;
+
+/// A claimed federation outbox row: the activity, who sends it and where.
+pub struct DueActivity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub inbox_url: String,
+    pub activity: serde_json::Value,
+    pub attempts: i32,
+}
+
+pub async fn find_actor_key(client: &Client, user_id: Uuid) -> Result<Option<ActorKey>, StorageError> {
+    let row = client
+        .query_opt(
+            "SELECT public_key_pem, private_key_pem FROM actor_keys WHERE user_id = $1",
+            &[&user_id],
+        )
+        .await?;
+    Ok(row.map(|row| ActorKey {
+        public_key_pem: row.get(0),
+        private_key_pem: row.get(1),
+    }))
+}
+
+/// Stores a freshly generated key unless a concurrent request got there first; returns the key that won.
+pub async fn insert_actor_key(client: &Client, user_id: Uuid, key: &ActorKey) -> Result<ActorKey, StorageError> {
+    client
+        .execute(
+            "INSERT INTO actor_keys (user_id, public_key_pem, private_key_pem, created_at) VALUES ($1, $2, $3, NOW())\n             ON CONFLICT (user_id) DO NOTHING",
+            &[&user_id, &key.public_key_pem, &key.private_key_pem],
+        )
+        .await?;
+    find_actor_key(client, user_id)
+        .await?
+        .ok_or_else(|| "actor_key_missing".into())
+}
+
+pub async fn find_remote_actor_by_key_id(client: &Client, key_id: &str) -> Result<Option<RemoteActor>, StorageError> {
+    let row = client
+        .query_opt(
+            "SELECT id, actor_uri, inbox_url, public_key_pem FROM remote_actors WHERE key_id = $1",
+            &[&key_id],
+        )
+        .await?;
+    Ok(row.as_ref().map(map_remote_actor))
+}
+
+pub async fn upsert_remote_actor(
+    client: &Client,
+    actor_uri: &str,
+    inbox_url: &str,
+    shared_inbox_url: Option<&str>,
+    key_id: &str,
+    public_key_pem: &str,
+) -> Result<RemoteActor, StorageError> {
+    let row = client
+        .query_one(
+            "INSERT INTO remote_actors (id, actor_uri, inbox_url, shared_inbox_url, key_id, public_key_pem, fetched_at)\n             VALUES ($1, $2, $3, $4, $5, $6, NOW())\n             ON CONFLICT (actor_uri) DO UPDATE SET inbox_url = EXCLUDED.inbox_url, shared_inbox_url = EXCLUDED.shared_inbox_url,\n                 key_id = EXCLUDED.key_id, public_key_pem = EXCLUDED.public_key_pem, fetched_at = NOW()\n             RETURNING id, actor_uri, inbox_url, public_key_pem",
+            &[&Uuid::new_v4(), &actor_uri, &inbox_url, &shared_inbox_url, &key_id, &public_key_pem],
+        )
+        .await?;
+    Ok(map_remote_actor(&row))
+}
+
+/// Records a remote follow; a repeated Follow only refreshes the activity id used to match its Undo.
+pub async fn create_remote_follow(
+    client: &Client,
+    remote_follower_id: Uuid,
+    followee_id: Uuid,
+    activity_id: Option<&str>,
+) -> Result<(), StorageError> {
+    client
+        .execute(
+            "INSERT INTO follows (remote_follower_id, followee_id, remote_activity_id, created_at) VALUES ($1, $2, $3, NOW())\n             ON CONFLICT (remote_follower_id, followee_id) DO UPDATE SET remote_activity_id = EXCLUDED.remote_activity_id",
+            &[&remote_follower_id, &followee_id, &activity_id],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Deletes a remote follow; with an activity id only the follow created by that activity matches.
+pub async fn delete_remote_follow(
+    client: &Client,
+    remote_follower_id: Uuid,
+    followee_id: Uuid,
+    activity_id: Option<&str>,
+) -> Result<bool, StorageError> {
+    let count = client
+        .execute(
+            "DELETE FROM follows WHERE remote_follower_id = $1 AND followee_id = $2\n               AND ($3::text IS NULL OR remote_activity_id = $3)",
+            &[&remote_follower_id, &followee_id, &activity_id],
+        )
+        .await?;
+    Ok(count > 0)
+}
+
+pub async fn count_remote_followers(client: &Client, user_id: Uuid) -> Result<i64, StorageError> {
+    let row = client
+        .query_one(
+            "SELECT COUNT(*) FROM follows WHERE followee_id = $1 AND remote_follower_id IS NOT NULL",
+            &[&user_id],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+/// Distinct inboxes of a user's remote followers, preferring each server's shared inbox.
+pub async fn list_follower_inboxes(client: &Client, user_id: Uuid) -> Result<Vec<String>, StorageError> {
+    let rows = client
+        .query(
+            "SELECT DISTINCT COALESCE(r.shared_inbox_url, r.inbox_url)\n             FROM follows f\n             JOIN remote_actors r ON r.id = f.remote_follower_id\n             WHERE f.followee_id = $1",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+/// Queues one activity per inbox; the federation dispatcher signs and sends them.
+pub async fn enqueue_federation_deliveries(
+    client: &Client,
+    user_id: Uuid,
+    inboxes: &[String],
+    activity: &serde_json::Value,
+) -> Result<u64, StorageError> {
+    let count = client
+        .execute(
+            "INSERT INTO federation_deliveries (id, user_id, inbox_url, activity, created_at)\n             SELECT uuid_generate_v4(), $1, inbox, $3, NOW() FROM unnest($2::text[]) AS inbox",
+            &[&user_id, &inboxes, &activity],
+        )
+        .await?;
+    Ok(count)
+}
+
+/// Claims due deliveries and pushes their next attempt out by `lease_secs`, so a crashed sender
+/// is retried later and concurrent instances skip rows another instance holds.
+pub async fn claim_federation_deliveries(
+    client: &Client,
+    limit: i64,
+    lease_secs: i32,
+) -> Result<Vec<DueActivity>, StorageError> {
+    let rows = client
+        .query(
+            "UPDATE federation_deliveries\n             SET attempts = attempts + 1, next_attempt_at = NOW() + $2::int * INTERVAL '1 second'\n             WHERE id IN (\n                 SELECT id FROM federation_deliveries\n                 WHERE status = 'pending' AND next_attempt_at <= NOW()\n                 ORDER BY next_attempt_at\n                 LIMIT $1\n                 FOR UPDATE SKIP LOCKED)\n             RETURNING id, user_id, inbox_url, activity, attempts",
+            &[&limit, &lease_secs],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| DueActivity {
+            id: row.get(0),
+            user_id: row.get(1),
+            inbox_url: row.get(2),
+            activity: row.get(3),
+            attempts: row.get(4),
+        })
+        .collect())
+}
+
+pub async fn complete_federation_delivery(client: &Client, delivery_id: Uuid, status_code: i32) -> Result<(), StorageError> {
+    client
+        .execute(
+            "UPDATE federation_deliveries SET status = 'delivered', delivered_at = NOW(), last_status_code = $2, last_error = NULL WHERE id = $1",
+            &[&delivery_id, &status_code],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Records a failed attempt; without `retry_in_secs` the delivery is given up as failed.
+pub async fn fail_federation_delivery(
+    client: &Client,
+    delivery_id: Uuid,
+    status_code: Option<i32>,
+    error: &str,
+    retry_in_secs: Option<i32>,
+) -> Result<(), StorageError> {
+    client
+        .execute(
+            "UPDATE federation_deliveries SET last_status_code = $2, last_error = $3,\n                 status = CASE WHEN $4::int IS NULL THEN 'failed' ELSE 'pending' END,\n                 next_attempt_at = CASE WHEN $4::int IS NULL THEN next_attempt_at ELSE NOW() + $4::int * INTERVAL '1 second' END\n             WHERE id = $1",
+            &[&delivery_id, &status_code, &error, &retry_in_secs],
+        )
+        .await?;
+    Ok(())
+}
+
+fn map_remote_actor(row: &tokio_postgres::Row) -> RemoteActor {
+    RemoteActor {
+        id: row.get(0),
+        actor_uri: row.get(1),
+        inbox_url: row.get(2),
+        public_key_pem: row.get(3),
+    }
+}
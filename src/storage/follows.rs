@@ -1,15 +1,18 @@
-use tokio_postgres::Client;
+use tokio_postgres::{Client, GenericClient};
 use uuid::Uuid;
 
 use crate::domain::{FollowEdge, UserProfile};
 use crate::domain::note::format_timestamp;
 use crate::storage::StorageError;
 
-pub async fn create_follow(
-    client: &Client,
+pub async fn create_follow<C>(
+    client: &C,
     follower_id: Uuid,
     followee_id: Uuid,
-) -> Result<Option<time::OffsetDateTime>, StorageError> {
+) -> Result<Option<time::OffsetDateTime>, StorageError>
+where
+    C: GenericClient + Sync,
+{
     let row = client
         .query_opt(
             "INSERT INTO follows (follower_id, followee_id, created_at) VALUES ($1, $2, NOW())\n             ON CONFLICT (follower_id, followee_id) DO NOTHING\n             RETURNING created_at",
@@ -19,11 +22,14 @@ pub async fn create_follow(
     Ok(row.map(|r| r.get(0)))
 }
 
-pub async fn delete_follow(
-    client: &Client,
+pub async fn delete_follow<C>(
+    client: &C,
     follower_id: Uuid,
     followee_id: Uuid,
-) -> Result<bool, StorageError> {
+) -> Result<bool, StorageError>
+where
+    C: GenericClient + Sync,
+{
     let count = client
         .execute(
             "DELETE FROM follows WHERE follower_id = $1 AND followee_id = $2",
@@ -33,14 +39,51 @@ pub async fn delete_follow(
     Ok(count > 0)
 }
 
+pub async fn list_followee_ids(client: &Client, user_id: Uuid) -> Result<Vec<Uuid>, StorageError> {
+    let rows = client
+        .query("SELECT followee_id FROM follows WHERE follower_id = $1", &[&user_id])
+        .await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+/// When `follower_id` started following `followee_id`, or `None` if it does not.
+pub async fn find_follow(
+    client: &Client,
+    follower_id: Uuid,
+    followee_id: Uuid,
+) -> Result<Option<time::OffsetDateTime>, StorageError> {
+    let row = client
+        .query_opt(
+            "SELECT created_at FROM follows WHERE follower_id = $1 AND followee_id = $2",
+            &[&follower_id, &followee_id],
+        )
+        .await?;
+    Ok(row.map(|row| row.get(0)))
+}
+
+/// Returns (followers, following) for a user, counting local accounts only.
+pub async fn count_follows(client: &Client, user_id: Uuid) -> Result<(i64, i64), StorageError> {
+    let row = client
+        .query_one(
+            "SELECT (SELECT COUNT(*) FROM follows WHERE followee_id = $1 AND follower_id IS NOT NULL), \
+                    (SELECT COUNT(*) FROM follows WHERE follower_id = $1)",
+            &[&user_id],
+        )
+        .await?;
+    Ok((row.get(0), row.get(1)))
+}
+
 pub async fn list_followers(
     client: &Client,
     user_id: Uuid,
+    cursor: Option<(time::OffsetDateTime, Uuid)>,
+    limit: i64,
 ) -> Result<Vec<FollowEdge>, StorageError> {
+    let (cursor_at, cursor_id) = cursor.unzip();
     let rows = client
         .query(
-            "SELECT u.user_id, u.email, u.account_note_id, f.created_at\n             FROM follows f\n             JOIN users u ON u.user_id = f.follower_id\n             WHERE f.followee_id = $1\n             ORDER BY f.created_at DESC",
-            &[&user_id],
+            "SELECT u.user_id, u.handle, u.display_name, u.account_note_id, f.created_at\n             FROM follows f\n             JOIN users u ON u.user_id = f.follower_id\n             WHERE f.followee_id = $1\n               AND ($2::timestamptz IS NULL OR (f.created_at, u.user_id) < ($2, $3))\n             ORDER BY f.created_at DESC, u.user_id DESC\n             LIMIT $4",
+            &[&user_id, &cursor_at, &cursor_id, &limit],
         )
         .await?;
     Ok(rows.iter().map(map_edge).collect())
@@ -49,26 +92,31 @@ pub async fn list_followers(
 pub async fn list_following(
     client: &Client,
     user_id: Uuid,
+    cursor: Option<(time::OffsetDateTime, Uuid)>,
+    limit: i64,
 ) -> Result<Vec<FollowEdge>, StorageError> {
+    let (cursor_at, cursor_id) = cursor.unzip();
     let rows = client
         .query(
-            "SELECT u.user_id, u.email, u.account_note_id, f.created_at\n             FROM follows f\n             JOIN users u ON u.user_id = f.followee_id\n             WHERE f.follower_id = $1\n             ORDER BY f.created_at DESC",
-            &[&user_id],
+            "SELECT u.user_id, u.handle, u.display_name, u.account_note_id, f.created_at\n             FROM follows f\n             JOIN users u ON u.user_id = f.followee_id\n             WHERE f.follower_id = $1\n               AND ($2::timestamptz IS NULL OR (f.created_at, u.user_id) < ($2, $3))\n             ORDER BY f.created_at DESC, u.user_id DESC\n             LIMIT $4",
+            &[&user_id, &cursor_at, &cursor_id, &limit],
         )
         .await?;
     Ok(rows.iter().map(map_edge).collect())
 }
 
-fn map_edge(row: &tokio_postgres::Row) -> FollowEdge {
+pub fn map_edge(row: &tokio_postgres::Row) -> FollowEdge {
     let user_id: Uuid = row.get(0);
-    let email: String = row.get(1);
-    let account_note_id = map_account_note_id(row.get(2));
-    let created_at: time::OffsetDateTime = row.get(3);
+    let handle: String = row.get(1);
+    let display_name: Option<String> = row.get(2);
+    let account_note_id = map_account_note_id(row.get(3));
+    let created_at: time::OffsetDateTime = row.get(4);
 
     FollowEdge {
         user: UserProfile {
             user_id,
-            email,
+            handle,
+            display_name,
             account_note_id,
         },
         created_at: format_timestamp(created_at),
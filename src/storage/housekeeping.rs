@@ -0,0 +1,50 @@
+use tokio_postgres::Client;
+
+use crate::storage::StorageError;
+
+#[derive(Clone, Copy, Default)]
+pub struct SweepCounts {
+    pub sessions: u64,
+    pub refresh_tokens: u64,
+    pub api_tokens: u64,
+    pub webhook_deliveries: u64,
+    pub federation_deliveries: u64,
+}
+
+impl SweepCounts {
+    pub fn total(&self) -> u64 {
+        self.sessions + self.refresh_tokens + self.api_tokens + self.webhook_deliveries + self.federation_deliveries
+    }
+}
+
+/// Deletes rows that can no longer authenticate anyone, plus settled webhook and federation deliveries after 30 days.
+pub async fn purge_expired(client: &Client) -> Result<SweepCounts, StorageError> {
+    let sessions = client
+        .execute("DELETE FROM sessions WHERE expires_at <= NOW()", &[])
+        .await?;
+    let refresh_tokens = client
+        .execute("DELETE FROM refresh_tokens WHERE expires_at <= NOW()", &[])
+        .await?;
+    let api_tokens = client
+        .execute("DELETE FROM api_tokens WHERE expires_at <= NOW()", &[])
+        .await?;
+    let webhook_deliveries = client
+        .execute(
+            "DELETE FROM webhook_deliveries WHERE status <> 'pending' AND created_at <= NOW() - INTERVAL '30 days'",
+            &[],
+        )
+        .await?;
+    let federation_deliveries = client
+        .execute(
+            "DELETE FROM federation_deliveries WHERE status <> 'pending' AND created_at <= NOW() - INTERVAL '30 days'",
+            &[],
+        )
+        .await?;
+    Ok(SweepCounts {
+        sessions,
+        refresh_tokens,
+        api_tokens,
+        webhook_deliveries,
+        federation_deliveries,
+    })
+}
@@ -0,0 +1,106 @@
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+use crate::domain::note::format_timestamp;
+use crate::domain::UserIdentity;
+use crate::storage::StorageError;
+
+pub enum LinkOutcome {
+    Linked(UserIdentity),
+    ProviderLinked,
+    TakenByOtherUser,
+}
+
+pub enum UnlinkOutcome {
+    Unlinked,
+    NotFound,
+    LastIdentity,
+}
+
+pub async fn list_identities(
+    client: &Client,
+    user_id: Uuid,
+) -> Result<Vec<UserIdentity>, StorageError> {
+    let rows = client
+        .query(
+            "SELECT provider, subject, email, created_at FROM user_identities \
+             WHERE user_id = $1 ORDER BY created_at, provider",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(map_identity).collect())
+}
+
+pub async fn link_identity(
+    client: &Client,
+    user_id: Uuid,
+    provider: &str,
+    subject: &str,
+    email: &str,
+) -> Result<LinkOutcome, StorageError> {
+    let existing = client
+        .query_opt(
+            "SELECT user_id FROM user_identities WHERE provider = $1 AND subject = $2",
+            &[&provider, &subject],
+        )
+        .await?;
+    if let Some(row) = existing {
+        let owner: Uuid = row.get(0);
+        return Ok(if owner == user_id {
+            LinkOutcome::ProviderLinked
+        } else {
+            LinkOutcome::TakenByOtherUser
+        });
+    }
+    let row = client
+        .query_opt(
+            "INSERT INTO user_identities (provider, subject, user_id, email, created_at) \
+             VALUES ($1, $2, $3, $4, NOW()) \
+             ON CONFLICT DO NOTHING \
+             RETURNING provider, subject, email, created_at",
+            &[&provider, &subject, &user_id, &email],
+        )
+        .await?;
+    Ok(match row {
+        Some(row) => LinkOutcome::Linked(map_identity(&row)),
+        None => LinkOutcome::ProviderLinked,
+    })
+}
+
+/// Refuses to remove the last identity, which would leave the account unable to sign in.
+pub async fn unlink_identity(
+    client: &Client,
+    user_id: Uuid,
+    provider: &str,
+) -> Result<UnlinkOutcome, StorageError> {
+    let row = client
+        .query_one(
+            "SELECT COUNT(*), COUNT(*) FILTER (WHERE provider = $2) FROM user_identities WHERE user_id = $1",
+            &[&user_id, &provider],
+        )
+        .await?;
+    let total: i64 = row.get(0);
+    let matching: i64 = row.get(1);
+    if matching == 0 {
+        return Ok(UnlinkOutcome::NotFound);
+    }
+    if total <= 1 {
+        return Ok(UnlinkOutcome::LastIdentity);
+    }
+    client
+        .execute(
+            "DELETE FROM user_identities WHERE user_id = $1 AND provider = $2",
+            &[&user_id, &provider],
+        )
+        .await?;
+    Ok(UnlinkOutcome::Unlinked)
+}
+
+pub fn map_identity(row: &tokio_postgres::Row) -> UserIdentity {
+    UserIdentity {
+        provider: row.get(0),
+        subject: row.get(1),
+        email: row.get(2),
+        created_at: format_timestamp(row.get(3)),
+    }
+}
@@ -1,15 +1,36 @@
-mod associations; mod follows; mod migrations; mod notes; mod sessions; mod users;
+mod accounts; mod api_tokens; mod associations; mod chains; mod events; mod federation; mod follows; mod housekeeping; mod identities; mod migrations; mod notes; mod notifications; mod search; mod sessions; mod threads; mod users; mod webhooks;
+pub use crate::storage::accounts::ProfileUpdate;
 pub use crate::storage::associations::AssociationInsertError;
+pub use crate::storage::events::EVENTS_CHANNEL;
+pub use crate::storage::housekeeping::SweepCounts;
+pub use crate::storage::identities::{LinkOutcome, UnlinkOutcome};
+pub use crate::storage::sessions::RefreshOutcome;
+pub use crate::storage::federation::DueActivity;
+pub use crate::storage::webhooks::DueDelivery;
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
 use tokio_postgres::NoTls;
 use crate::config::Config;
-use crate::domain::{generate_note_id, Association, FollowEdge, Note, NoteId, User};
-use crate::storage::associations::{create_association, list_associations};
-use crate::storage::follows::{create_follow, delete_follow, list_followers, list_following};
-use crate::storage::notes::{create_note, find_note, find_notes_by_ids, insert_note, list_feed_notes, list_notes, list_random_notes};
-use crate::storage::sessions::{create_session, get_session_user};
-use crate::storage::users::{create_account_note, find_or_create_user, find_user_by_id, is_account_note_id};
+use crate::domain::{generate_note_id, ActorKey, ExportRecord, ApiToken, Association, AssociationRemoval, FollowEdge, Note, NoteId, Notification, RemoteActor, Session, StreamEvent, ThreadEdge, User, UserIdentity, Webhook, WebhookDelivery};
+use crate::domain::note::format_timestamp;
+use crate::storage::accounts::{delete_account, export_account, update_profile};
+use crate::storage::api_tokens::{create_api_token, delete_api_token, get_api_token_user, list_api_tokens};
+use crate::storage::associations::{create_association, delete_association, list_association_removals, list_associations, lock_association_target};
+use crate::storage::chains::{find_latest_version, find_note_chain, find_version_lineage};
+use crate::storage::events::notify_event;
+use crate::storage::federation::{claim_federation_deliveries, complete_federation_delivery, count_remote_followers, create_remote_follow, delete_remote_follow, enqueue_federation_deliveries, fail_federation_delivery, find_actor_key, find_remote_actor_by_key_id, insert_actor_key, list_follower_inboxes, upsert_remote_actor};
+use crate::storage::follows::{count_follows, create_follow, delete_follow, find_follow, list_followee_ids, list_followers, list_following};
+use crate::storage::housekeeping::purge_expired;
+use crate::storage::identities::{link_identity, list_identities, unlink_identity};
+use crate::storage::notes::{create_note, find_note, find_note_authors, find_notes_by_ids, insert_note, list_author_posts, list_feed_notes, list_notes, list_random_notes, retract_notes};
+use crate::storage::notifications::{count_unread_notifications, delete_note_notifications, insert_follow_notification, insert_note_notification, insert_version_notifications, list_notifications, mark_notifications_read};
+use crate::storage::search::search_notes;
+use crate::auth::sessions::SessionLifetime;
+use crate::storage::sessions::{create_refresh_token, create_session, delete_other_sessions, delete_session, delete_session_by_token, get_session_user, list_sessions, rotate_refresh_token};
+use crate::storage::threads::{find_thread_ancestors, find_thread_descendants};
+use crate::storage::users::{find_or_create_user, find_user_by_handle, find_user_by_id, list_account_note_history, lock_account_note, replace_account_note};
+use crate::storage::webhooks::{claim_webhook_deliveries, complete_webhook_delivery, create_webhook, delete_webhook, enqueue_webhook_event, fail_webhook_delivery, list_webhook_deliveries, list_webhooks};
 use crate::urls::base32::encode_id;
+use serde_json::json;
 
 pub type StorageError = Box<dyn std::error::Error + Send + Sync>;
 
@@ -24,14 +45,30 @@ impl Storage {
     }
     pub async fn run_migrations(&self, path: &str) -> Result<(), StorageError> { migrations::run(&self.pool, path).await }
     pub async fn healthcheck(&self) -> Result<(), StorageError> { let client = self.pool.get().await?; client.query_one("SELECT 1", &[]).await?; Ok(()) }
-    pub async fn find_or_create_user(&self, google_sub: &str, email: &str) -> Result<User, StorageError> { let client = self.pool.get().await?; find_or_create_user(&client, google_sub, email).await }
-    pub async fn create_session(&self, user_id: uuid::Uuid, token: &str, expires_at: time::OffsetDateTime) -> Result<(), StorageError> { let client = self.pool.get().await?; create_session(&client, user_id, token, expires_at).await }
-    pub async fn get_session_user(&self, token: &str) -> Result<Option<User>, StorageError> { let client = self.pool.get().await?; get_session_user(&client, token).await }
+    pub async fn purge_expired(&self) -> Result<SweepCounts, StorageError> { let client = self.pool.get().await?; purge_expired(&client).await }
+    pub async fn find_or_create_user(&self, provider: &str, subject: &str, email: &str) -> Result<User, StorageError> { let mut client = self.pool.get().await?; let client_ref = &mut **client; find_or_create_user(client_ref, provider, subject, email).await }
+    pub async fn list_identities(&self, user_id: uuid::Uuid) -> Result<Vec<UserIdentity>, StorageError> { let client = self.pool.get().await?; list_identities(&client, user_id).await }
+    pub async fn link_identity(&self, user_id: uuid::Uuid, provider: &str, subject: &str, email: &str) -> Result<LinkOutcome, StorageError> { let client = self.pool.get().await?; link_identity(&client, user_id, provider, subject, email).await }
+    pub async fn unlink_identity(&self, user_id: uuid::Uuid, provider: &str) -> Result<UnlinkOutcome, StorageError> { let client = self.pool.get().await?; unlink_identity(&client, user_id, provider).await }
+    pub async fn create_session(&self, user_id: uuid::Uuid, token: &str, expires_at: time::OffsetDateTime) -> Result<uuid::Uuid, StorageError> { let client = self.pool.get().await?; create_session(&client, user_id, token, expires_at).await }
+    pub async fn create_refresh_token(&self, user_id: uuid::Uuid, family_id: uuid::Uuid, token: &str, expires_at: time::OffsetDateTime) -> Result<(), StorageError> { let client = self.pool.get().await?; create_refresh_token(&client, user_id, family_id, token, expires_at).await }
+    pub async fn get_session_user(&self, token: &str, lifetime: SessionLifetime) -> Result<Option<User>, StorageError> { let client = self.pool.get().await?; get_session_user(&client, token, lifetime).await }
+    pub async fn rotate_refresh_token(&self, refresh_token: &str, new_token: &str, new_refresh_token: &str, lifetime: SessionLifetime) -> Result<RefreshOutcome, StorageError> { let mut client = self.pool.get().await?; let client_ref = &mut **client; rotate_refresh_token(client_ref, refresh_token, new_token, new_refresh_token, lifetime).await }
+    pub async fn list_sessions(&self, user_id: uuid::Uuid, current_token: &str) -> Result<Vec<Session>, StorageError> { let client = self.pool.get().await?; list_sessions(&client, user_id, current_token).await }
+    pub async fn delete_session_by_token(&self, token: &str) -> Result<bool, StorageError> { let client = self.pool.get().await?; delete_session_by_token(&client, token).await }
+    pub async fn delete_session(&self, user_id: uuid::Uuid, session_id: uuid::Uuid) -> Result<bool, StorageError> { let client = self.pool.get().await?; delete_session(&client, user_id, session_id).await }
+    pub async fn delete_other_sessions(&self, user_id: uuid::Uuid, current_token: &str) -> Result<u64, StorageError> { let client = self.pool.get().await?; delete_other_sessions(&client, user_id, current_token).await }
+    pub async fn create_api_token(&self, user_id: uuid::Uuid, name: &str, token: &str, scopes: &[String], expires_at: Option<time::OffsetDateTime>) -> Result<ApiToken, StorageError> { let client = self.pool.get().await?; create_api_token(&client, user_id, name, token, scopes, expires_at).await }
+    pub async fn list_api_tokens(&self, user_id: uuid::Uuid) -> Result<Vec<ApiToken>, StorageError> { let client = self.pool.get().await?; list_api_tokens(&client, user_id).await }
+    pub async fn delete_api_token(&self, user_id: uuid::Uuid, token_id: uuid::Uuid) -> Result<bool, StorageError> { let client = self.pool.get().await?; delete_api_token(&client, user_id, token_id).await }
+    pub async fn get_api_token_user(&self, token: &str) -> Result<Option<(User, Vec<String>)>, StorageError> { let client = self.pool.get().await?; get_api_token_user(&client, token).await }
     pub async fn create_note(&self, note_id: NoteId, value: &[u8], author_id: uuid::Uuid) -> Result<Note, StorageError> {
         let mut client = self.pool.get().await?; let client_ref = &mut **client; create_note(client_ref, note_id, value, author_id).await
     }
-    pub async fn create_note_chain(&self, segments: &[Vec<u8>], author_id: uuid::Uuid, account_note_id: NoteId) -> Result<(Note, Vec<String>), StorageError> {
+    /// Creates a post and the reply, quote or link associations it starts with, all or nothing.
+    pub async fn create_note_chain(&self, segments: &[Vec<u8>], author_id: uuid::Uuid, links: &[(&str, NoteId)]) -> Result<(Note, Vec<String>), StorageError> {
         let mut client = self.pool.get().await?; let client_ref = &mut **client; let transaction = client_ref.transaction().await?;
+        let account_note_id = lock_account_note(&transaction, author_id).await?;
         let mut ids = Vec::with_capacity(segments.len()); let mut root_note = None; let mut prev_id: Option<NoteId> = None;
         for (index, segment) in segments.iter().enumerate() {
             let note_id = generate_note_id();
@@ -42,13 +79,22 @@ impl Storage {
             if let Some(prev) = prev_id { create_association(&transaction, "next", prev, note_id).await?; }
             prev_id = Some(note_id); ids.push(note_id);
         }
-        transaction.commit().await?;
         let root = root_note.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "missing_root_note"))?;
-        let segments = ids.iter().map(|id| encode_id(id.to_bytes())).collect();
+        let segments: Vec<String> = ids.iter().map(|id| encode_id(id.to_bytes())).collect();
+        enqueue_webhook_event(&transaction, "note.created", &[author_id], json!({"note": &root, "segments": &segments})).await?;
+        notify_event(&transaction, &StreamEvent::Post { note_id: root.id.clone(), author_id, supersedes: None }).await?;
+        let root_id = ids[0];
+        for (kind, target_id) in links {
+            lock_association_target(&transaction, *target_id).await?;
+            let association = create_association(&transaction, kind, root_id, *target_id).await?;
+            publish_association(&transaction, &association, root_id, *target_id).await?;
+        }
+        transaction.commit().await?;
         Ok((root, segments))
     }
-    pub async fn create_note_version_chain(&self, source_id: NoteId, segments: &[Vec<u8>], author_id: uuid::Uuid, account_note_id: NoteId) -> Result<(Note, Vec<String>), StorageError> {
+    pub async fn create_note_version_chain(&self, source_id: NoteId, segments: &[Vec<u8>], author_id: uuid::Uuid) -> Result<(Note, Vec<String>), StorageError> {
         let mut client = self.pool.get().await?; let client_ref = &mut **client; let transaction = client_ref.transaction().await?;
+        let account_note_id = lock_account_note(&transaction, author_id).await?;
         let source_bytes = source_id.to_bytes();
         let exists = transaction.query_opt(
             "SELECT 1 FROM associations WHERE kind = 'version' AND from_id = $1",
@@ -68,23 +114,120 @@ impl Storage {
         }
         let root = root_note.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "missing_root_note"))?;
         let root_id = root_id.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "missing_root_note_id"))?;
-        create_association(&transaction, "version", source_id, root_id).await?;
+        let version = create_association(&transaction, "version", source_id, root_id).await?;
+        insert_version_notifications(&transaction, source_id, root_id).await?;
+        let segments: Vec<String> = ids.iter().map(|id| encode_id(id.to_bytes())).collect();
+        let data = json!({"note": &root, "segments": &segments, "previous_id": encode_id(source_bytes)});
+        enqueue_webhook_event(&transaction, "note.versioned", &[author_id], data).await?;
+        notify_event(&transaction, &StreamEvent::Post { note_id: root.id.clone(), author_id, supersedes: Some(encode_id(source_bytes)) }).await?;
+        notify_event(&transaction, &StreamEvent::Association(version)).await?;
         transaction.commit().await?;
-        let segments = ids.iter().map(|id| encode_id(id.to_bytes())).collect();
         Ok((root, segments))
     }
     pub async fn find_note(&self, note_id: NoteId) -> Result<Option<Note>, StorageError> { let client = self.pool.get().await?; find_note(&client, note_id).await }
+    pub async fn find_note_chain(&self, note_id: NoteId) -> Result<Option<(Note, Vec<Note>, Vec<Note>)>, StorageError> { let client = self.pool.get().await?; find_note_chain(&client, note_id).await }
+    pub async fn find_version_lineage(&self, note_id: NoteId, max_depth: i32) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; find_version_lineage(&client, note_id, max_depth).await }
+    pub async fn find_latest_version(&self, note_id: NoteId, max_depth: i32) -> Result<Option<Note>, StorageError> { let client = self.pool.get().await?; find_latest_version(&client, note_id, max_depth).await }
+    pub async fn find_thread_descendants(&self, note_id: NoteId, max_depth: i32, breadth: i64, max_nodes: i64) -> Result<Option<(Note, i64, Vec<ThreadEdge>)>, StorageError> { let client = self.pool.get().await?; find_thread_descendants(&client, note_id, max_depth, breadth, max_nodes).await }
+    pub async fn find_thread_ancestors(&self, note_id: NoteId, max_depth: i32) -> Result<Vec<ThreadEdge>, StorageError> { let client = self.pool.get().await?; find_thread_ancestors(&client, note_id, max_depth).await }
     pub async fn find_notes_by_ids(&self, note_ids: &[NoteId]) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; find_notes_by_ids(&client, note_ids).await }
-    pub async fn list_notes(&self, author: Option<uuid::Uuid>, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_notes(&client, author, from, to).await }
-    pub async fn list_feed_notes(&self, user_id: uuid::Uuid, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, limit: i64) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_feed_notes(&client, user_id, from, to, limit).await }
+    pub async fn list_notes(&self, author: Option<uuid::Uuid>, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, cursor: Option<(time::OffsetDateTime, NoteId)>, limit: i64) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_notes(&client, author, from, to, cursor, limit).await }
+    pub async fn list_feed_notes(&self, user_id: uuid::Uuid, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, cursor: Option<(time::OffsetDateTime, NoteId)>, limit: i64) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_feed_notes(&client, user_id, from, to, cursor, limit).await }
+    pub async fn retract_notes(&self, note_ids: &[NoteId]) -> Result<u64, StorageError> { let client = self.pool.get().await?; retract_notes(&client, note_ids).await }
+    pub async fn search_notes(&self, query: &str, author: Option<uuid::Uuid>, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, limit: i64) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; search_notes(&client, query, author, from, to, limit).await }
+    pub async fn list_author_posts(&self, author_id: uuid::Uuid, cursor: Option<(time::OffsetDateTime, NoteId)>, limit: i64) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_author_posts(&client, author_id, cursor, limit).await }
     pub async fn list_random_notes(&self, limit: i64) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_random_notes(&client, limit).await }
-    pub async fn create_association(&self, kind: &str, from_id: NoteId, to_id: NoteId) -> Result<Association, StorageError> { let client = self.pool.get().await?; let client_ref = &**client; create_association(client_ref, kind, from_id, to_id).await }
+    pub async fn create_association(&self, kind: &str, from_id: NoteId, to_id: NoteId) -> Result<Association, StorageError> {
+        let mut client = self.pool.get().await?; let client_ref = &mut **client; let transaction = client_ref.transaction().await?;
+        let association = create_association(&transaction, kind, from_id, to_id).await?;
+        publish_association(&transaction, &association, from_id, to_id).await?;
+        transaction.commit().await?;
+        Ok(association)
+    }
     pub async fn list_associations(&self, note_id: NoteId) -> Result<Vec<Association>, StorageError> { let client = self.pool.get().await?; list_associations(&client, note_id).await }
-    pub async fn create_follow(&self, follower_id: uuid::Uuid, followee_id: uuid::Uuid) -> Result<Option<time::OffsetDateTime>, StorageError> { let client = self.pool.get().await?; create_follow(&client, follower_id, followee_id).await }
-    pub async fn delete_follow(&self, follower_id: uuid::Uuid, followee_id: uuid::Uuid) -> Result<bool, StorageError> { let client = self.pool.get().await?; delete_follow(&client, follower_id, followee_id).await }
-    pub async fn list_followers(&self, user_id: uuid::Uuid) -> Result<Vec<FollowEdge>, StorageError> { let client = self.pool.get().await?; list_followers(&client, user_id).await }
-    pub async fn list_following(&self, user_id: uuid::Uuid) -> Result<Vec<FollowEdge>, StorageError> { let client = self.pool.get().await?; list_following(&client, user_id).await }
+    pub async fn delete_association(&self, kind: &str, from_id: NoteId, to_id: NoteId, removed_by: uuid::Uuid) -> Result<Vec<AssociationRemoval>, StorageError> {
+        let mut client = self.pool.get().await?; let client_ref = &mut **client; let transaction = client_ref.transaction().await?;
+        let removals = delete_association(&transaction, kind, from_id, to_id, removed_by).await?;
+        if !removals.is_empty() {
+            if matches!(kind, "reply" | "quote") { delete_note_notifications(&transaction, kind, from_id, to_id).await?; }
+            let owners = find_note_authors(&transaction, &[from_id, to_id]).await?;
+            enqueue_webhook_event(&transaction, "association.deleted", &owners, json!({"removals": &removals})).await?;
+        }
+        transaction.commit().await?;
+        Ok(removals)
+    }
+    pub async fn list_association_removals(&self, note_id: NoteId) -> Result<Vec<AssociationRemoval>, StorageError> { let client = self.pool.get().await?; list_association_removals(&client, note_id).await }
+    pub async fn create_follow(&self, follower_id: uuid::Uuid, followee_id: uuid::Uuid) -> Result<Option<time::OffsetDateTime>, StorageError> {
+        let mut client = self.pool.get().await?; let client_ref = &mut **client; let transaction = client_ref.transaction().await?;
+        let created_at = create_follow(&transaction, follower_id, followee_id).await?;
+        if let Some(created_at) = created_at {
+            let data = json!({"follower_id": follower_id, "followee_id": followee_id, "created_at": format_timestamp(created_at)});
+            enqueue_webhook_event(&transaction, "follow.created", &[follower_id, followee_id], data).await?;
+            insert_follow_notification(&transaction, follower_id, followee_id).await?;
+            notify_event(&transaction, &StreamEvent::Follow { follower_id, followee_id, following: true }).await?;
+        }
+        transaction.commit().await?;
+        Ok(created_at)
+    }
+    pub async fn delete_follow(&self, follower_id: uuid::Uuid, followee_id: uuid::Uuid) -> Result<bool, StorageError> {
+        let mut client = self.pool.get().await?; let client_ref = &mut **client; let transaction = client_ref.transaction().await?;
+        let deleted = delete_follow(&transaction, follower_id, followee_id).await?;
+        if deleted {
+            let data = json!({"follower_id": follower_id, "followee_id": followee_id});
+            enqueue_webhook_event(&transaction, "follow.deleted", &[follower_id, followee_id], data).await?;
+            notify_event(&transaction, &StreamEvent::Follow { follower_id, followee_id, following: false }).await?;
+        }
+        transaction.commit().await?;
+        Ok(deleted)
+    }
+    pub async fn count_follows(&self, user_id: uuid::Uuid) -> Result<(i64, i64), StorageError> { let client = self.pool.get().await?; count_follows(&client, user_id).await }
+    pub async fn find_follow(&self, follower_id: uuid::Uuid, followee_id: uuid::Uuid) -> Result<Option<time::OffsetDateTime>, StorageError> { let client = self.pool.get().await?; find_follow(&client, follower_id, followee_id).await }
+    pub async fn list_followee_ids(&self, user_id: uuid::Uuid) -> Result<Vec<uuid::Uuid>, StorageError> { let client = self.pool.get().await?; list_followee_ids(&client, user_id).await }
+    pub async fn list_followers(&self, user_id: uuid::Uuid, cursor: Option<(time::OffsetDateTime, uuid::Uuid)>, limit: i64) -> Result<Vec<FollowEdge>, StorageError> { let client = self.pool.get().await?; list_followers(&client, user_id, cursor, limit).await }
+    pub async fn list_following(&self, user_id: uuid::Uuid, cursor: Option<(time::OffsetDateTime, uuid::Uuid)>, limit: i64) -> Result<Vec<FollowEdge>, StorageError> { let client = self.pool.get().await?; list_following(&client, user_id, cursor, limit).await }
+    pub async fn find_actor_key(&self, user_id: uuid::Uuid) -> Result<Option<ActorKey>, StorageError> { let client = self.pool.get().await?; find_actor_key(&client, user_id).await }
+    pub async fn insert_actor_key(&self, user_id: uuid::Uuid, key: &ActorKey) -> Result<ActorKey, StorageError> { let client = self.pool.get().await?; insert_actor_key(&client, user_id, key).await }
+    pub async fn find_remote_actor_by_key_id(&self, key_id: &str) -> Result<Option<RemoteActor>, StorageError> { let client = self.pool.get().await?; find_remote_actor_by_key_id(&client, key_id).await }
+    pub async fn upsert_remote_actor(&self, actor_uri: &str, inbox_url: &str, shared_inbox_url: Option<&str>, key_id: &str, public_key_pem: &str) -> Result<RemoteActor, StorageError> { let client = self.pool.get().await?; upsert_remote_actor(&client, actor_uri, inbox_url, shared_inbox_url, key_id, public_key_pem).await }
+    pub async fn create_remote_follow(&self, remote_follower_id: uuid::Uuid, followee_id: uuid::Uuid, activity_id: Option<&str>) -> Result<(), StorageError> { let client = self.pool.get().await?; create_remote_follow(&client, remote_follower_id, followee_id, activity_id).await }
+    pub async fn delete_remote_follow(&self, remote_follower_id: uuid::Uuid, followee_id: uuid::Uuid, activity_id: Option<&str>) -> Result<bool, StorageError> { let client = self.pool.get().await?; delete_remote_follow(&client, remote_follower_id, followee_id, activity_id).await }
+    pub async fn count_remote_followers(&self, user_id: uuid::Uuid) -> Result<i64, StorageError> { let client = self.pool.get().await?; count_remote_followers(&client, user_id).await }
+    pub async fn list_follower_inboxes(&self, user_id: uuid::Uuid) -> Result<Vec<String>, StorageError> { let client = self.pool.get().await?; list_follower_inboxes(&client, user_id).await }
+    pub async fn enqueue_federation_deliveries(&self, user_id: uuid::Uuid, inboxes: &[String], activity: &serde_json::Value) -> Result<u64, StorageError> { let client = self.pool.get().await?; enqueue_federation_deliveries(&client, user_id, inboxes, activity).await }
+    pub async fn claim_federation_deliveries(&self, limit: i64, lease_secs: i32) -> Result<Vec<DueActivity>, StorageError> { let client = self.pool.get().await?; claim_federation_deliveries(&client, limit, lease_secs).await }
+    pub async fn complete_federation_delivery(&self, delivery_id: uuid::Uuid, status_code: i32) -> Result<(), StorageError> { let client = self.pool.get().await?; complete_federation_delivery(&client, delivery_id, status_code).await }
+    pub async fn fail_federation_delivery(&self, delivery_id: uuid::Uuid, status_code: Option<i32>, error: &str, retry_in_secs: Option<i32>) -> Result<(), StorageError> { let client = self.pool.get().await?; fail_federation_delivery(&client, delivery_id, status_code, error, retry_in_secs).await }
+    pub async fn list_notifications(&self, user_id: uuid::Uuid, unread_only: bool, cursor: Option<(time::OffsetDateTime, uuid::Uuid)>, limit: i64) -> Result<Vec<Notification>, StorageError> { let client = self.pool.get().await?; list_notifications(&client, user_id, unread_only, cursor, limit).await }
+    pub async fn count_unread_notifications(&self, user_id: uuid::Uuid) -> Result<i64, StorageError> { let client = self.pool.get().await?; count_unread_notifications(&client, user_id).await }
+    pub async fn mark_notifications_read(&self, user_id: uuid::Uuid, ids: Option<&[uuid::Uuid]>) -> Result<u64, StorageError> { let client = self.pool.get().await?; mark_notifications_read(&client, user_id, ids).await }
+    pub async fn create_webhook(&self, user_id: uuid::Uuid, url: &str, secret: &str, events: &[String]) -> Result<Webhook, StorageError> { let client = self.pool.get().await?; create_webhook(&client, user_id, url, secret, events).await }
+    pub async fn list_webhooks(&self, user_id: uuid::Uuid) -> Result<Vec<Webhook>, StorageError> { let client = self.pool.get().await?; list_webhooks(&client, user_id).await }
+    pub async fn delete_webhook(&self, user_id: uuid::Uuid, webhook_id: uuid::Uuid) -> Result<bool, StorageError> { let client = self.pool.get().await?; delete_webhook(&client, user_id, webhook_id).await }
+    pub async fn list_webhook_deliveries(&self, user_id: uuid::Uuid, webhook_id: uuid::Uuid, cursor: Option<(time::OffsetDateTime, uuid::Uuid)>, limit: i64) -> Result<Option<Vec<WebhookDelivery>>, StorageError> { let client = self.pool.get().await?; list_webhook_deliveries(&client, user_id, webhook_id, cursor, limit).await }
+    pub async fn claim_webhook_deliveries(&self, limit: i64, lease_secs: i32) -> Result<Vec<DueDelivery>, StorageError> { let client = self.pool.get().await?; claim_webhook_deliveries(&client, limit, lease_secs).await }
+    pub async fn complete_webhook_delivery(&self, delivery_id: uuid::Uuid, status_code: i32) -> Result<(), StorageError> { let client = self.pool.get().await?; complete_webhook_delivery(&client, delivery_id, status_code).await }
+    pub async fn fail_webhook_delivery(&self, delivery_id: uuid::Uuid, status_code: Option<i32>, error: &str, retry_in_secs: Option<i32>) -> Result<(), StorageError> { let client = self.pool.get().await?; fail_webhook_delivery(&client, delivery_id, status_code, error, retry_in_secs).await }
+    pub async fn find_user_by_handle(&self, handle: &str) -> Result<Option<User>, StorageError> { let client = self.pool.get().await?; find_user_by_handle(&client, handle).await }
     pub async fn find_user_by_id(&self, user_id: uuid::Uuid) -> Result<Option<User>, StorageError> { let client = self.pool.get().await?; find_user_by_id(&client, user_id).await }
-    pub async fn create_account_note(&self, user_id: uuid::Uuid, value: &[u8]) -> Result<Note, StorageError> { let client = self.pool.get().await?; create_account_note(&client, user_id, value).await }
-    pub async fn is_account_note_id(&self, note_id: NoteId) -> Result<bool, StorageError> { let client = self.pool.get().await?; is_account_note_id(&client, note_id).await }
+    pub async fn replace_account_note(&self, user_id: uuid::Uuid, value: &[u8]) -> Result<Note, StorageError> { let mut client = self.pool.get().await?; let client_ref = &mut **client; replace_account_note(client_ref, user_id, value).await }
+    pub async fn list_account_note_history(&self, user_id: uuid::Uuid) -> Result<Vec<Note>, StorageError> { let client = self.pool.get().await?; list_account_note_history(&client, user_id).await }
+    pub async fn export_account(&self, user: &User, records: &tokio::sync::mpsc::Sender<ExportRecord>) -> Result<(), StorageError> { let mut client = self.pool.get().await?; let client_ref = &mut **client; export_account(client_ref, user, records).await }
+    pub async fn update_profile(&self, user_id: uuid::Uuid, handle: Option<&str>, display_name: Option<&str>) -> Result<Option<ProfileUpdate>, StorageError> { let client = self.pool.get().await?; update_profile(&client, user_id, handle, display_name).await }
+    pub async fn delete_account(&self, user_id: uuid::Uuid) -> Result<u64, StorageError> { let mut client = self.pool.get().await?; let client_ref = &mut **client; delete_account(client_ref, user_id).await }
+}
+
+/// Notifications, webhook event and live update for a new association, inside its transaction.
+async fn publish_association<C>(client: &C, association: &Association, from_id: NoteId, to_id: NoteId) -> Result<(), StorageError>
+where
+    C: tokio_postgres::GenericClient + Sync,
+{
+    match association.kind.as_str() {
+        "reply" | "quote" => insert_note_notification(client, &association.kind, from_id, to_id).await?,
+        "version" => insert_version_notifications(client, from_id, to_id).await?,
+        _ => {}
+    }
+    let owners = find_note_authors(client, &[from_id, to_id]).await?;
+    enqueue_webhook_event(client, "association.created", &owners, json!({"association": association})).await?;
+    notify_event(client, &StreamEvent::Association(association.clone())).await?;
+    Ok(())
 }
@@ -10,7 +10,7 @@ where C: GenericClient + Sync {
     insert_note(client, note_id, value, author_id).await?;
     let id_bytes = note_id.to_bytes();
     let row = client.query_one(
-        "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.id = $1",
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.id = $1",
         &[&id_bytes.to_vec()],
     ).await?;
     Ok(map_note(&row))
@@ -26,33 +26,55 @@ where C: GenericClient + Sync {
     Ok(())
 }
 
+/// Distinct authors of the given notes.
+pub async fn find_note_authors<C>(client: &C, note_ids: &[NoteId]) -> Result<Vec<Uuid>, StorageError>
+where C: GenericClient + Sync {
+    let ids: Vec<Vec<u8>> = note_ids.iter().map(|note_id| note_id.to_bytes().to_vec()).collect();
+    let rows = client.query("SELECT DISTINCT author_id FROM notes WHERE id = ANY($1)", &[&ids]).await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
 pub async fn find_note(client: &Client, note_id: NoteId) -> Result<Option<Note>, StorageError> {
     let id_bytes = note_id.to_bytes();
     let row = client.query_opt(
-        "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.id = $1",
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.id = $1",
         &[&id_bytes.to_vec()],
     ).await?;
     Ok(row.map(|r| map_note(&r)))
 }
 
-pub async fn list_notes(client: &Client, author: Option<Uuid>, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>) -> Result<Vec<Note>, StorageError> {
-    let mut clauses = Vec::new();
+pub async fn list_notes(client: &Client, author: Option<Uuid>, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, cursor: Option<(time::OffsetDateTime, NoteId)>, limit: i64) -> Result<Vec<Note>, StorageError> {
+    let mut clauses = vec!["n.retracted_at IS NULL".to_string()];
     let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
-    if let Some(author_id) = author.as_ref() { clauses.push(format!("author_id = ${}", params.len() + 1)); params.push(author_id); }
-    if let Some(from_ts) = from.as_ref() { clauses.push(format!("created_at >= ${}", params.len() + 1)); params.push(from_ts); }
-    if let Some(to_ts) = to.as_ref() { clauses.push(format!("created_at <= ${}", params.len() + 1)); params.push(to_ts); }
-    let where_clause = if clauses.is_empty() { "".to_string() } else { format!("WHERE {}", clauses.join(" AND ")) };
+    if let Some(author_id) = author.as_ref() { clauses.push(format!("n.author_id = ${}", params.len() + 1)); params.push(author_id); }
+    if let Some(from_ts) = from.as_ref() { clauses.push(format!("n.created_at >= ${}", params.len() + 1)); params.push(from_ts); }
+    if let Some(to_ts) = to.as_ref() { clauses.push(format!("n.created_at <= ${}", params.len() + 1)); params.push(to_ts); }
+    let cursor = cursor.map(|(created_at, note_id)| (created_at, note_id.to_bytes().to_vec()));
+    if let Some((created_at, id_bytes)) = cursor.as_ref() { clauses.push(format!("(n.created_at, n.id) < (${}, ${})", params.len() + 1, params.len() + 2)); params.push(created_at); params.push(id_bytes); }
+    let limit_idx = params.len() + 1; params.push(&limit);
     let query = format!(
-        "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id FROM notes n JOIN users u ON u.user_id = n.author_id {} ORDER BY n.created_at DESC",
-        where_clause
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE {} ORDER BY n.created_at DESC, n.id DESC LIMIT ${}",
+        clauses.join(" AND "),
+        limit_idx
     );
     let rows = client.query(&query, &params).await?;
     Ok(rows.iter().map(map_note).collect())
 }
 
+/// Lists an author's posts (chain heads that are not superseded or retracted), newest first.
+pub async fn list_author_posts(client: &Client, author_id: Uuid, cursor: Option<(time::OffsetDateTime, NoteId)>, limit: i64) -> Result<Vec<Note>, StorageError> {
+    let cursor = cursor.map(|(created_at, note_id)| (created_at, note_id.to_bytes().to_vec()));
+    let (cursor_at, cursor_id) = cursor.unzip();
+    let rows = client.query(
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.author_id = $1 AND n.retracted_at IS NULL AND NOT EXISTS (SELECT 1 FROM associations a WHERE (a.kind = 'next' AND a.to_id = n.id) OR (a.kind = 'prev' AND a.from_id = n.id)) AND NOT EXISTS (SELECT 1 FROM associations a WHERE a.kind = 'version' AND a.from_id = n.id) AND ($2::timestamptz IS NULL OR (n.created_at, n.id) < ($2, $3)) ORDER BY n.created_at DESC, n.id DESC LIMIT $4",
+        &[&author_id, &cursor_at, &cursor_id, &limit],
+    ).await?;
+    Ok(rows.iter().map(map_note).collect())
+}
+
 pub async fn list_random_notes(client: &Client, limit: i64) -> Result<Vec<Note>, StorageError> {
     let rows = client.query(
-        "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id FROM notes n JOIN users u ON u.user_id = n.author_id WHERE NOT EXISTS (SELECT 1 FROM associations a WHERE (a.kind = 'next' AND a.to_id = n.id) OR (a.kind = 'prev' AND a.from_id = n.id)) AND NOT EXISTS (SELECT 1 FROM associations a WHERE a.kind = 'version' AND a.from_id = n.id) ORDER BY RANDOM() LIMIT $1",
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.retracted_at IS NULL AND NOT EXISTS (SELECT 1 FROM associations a WHERE (a.kind = 'next' AND a.to_id = n.id) OR (a.kind = 'prev' AND a.from_id = n.id)) AND NOT EXISTS (SELECT 1 FROM associations a WHERE a.kind = 'version' AND a.from_id = n.id) ORDER BY RANDOM() LIMIT $1",
         &[&limit],
     ).await?;
     Ok(rows.iter().map(map_note).collect())
@@ -62,24 +84,27 @@ pub async fn find_notes_by_ids(client: &Client, note_ids: &[NoteId]) -> Result<V
     if note_ids.is_empty() { return Ok(Vec::new()); }
     let ids: Vec<Vec<u8>> = note_ids.iter().map(|note_id| note_id.to_bytes().to_vec()).collect();
     let rows = client.query(
-        "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.id = ANY($1)",
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE n.id = ANY($1)",
         &[&ids],
     ).await?;
     Ok(rows.iter().map(map_note).collect())
 }
 
-pub async fn list_feed_notes(client: &Client, user_id: Uuid, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, limit: i64) -> Result<Vec<Note>, StorageError> {
+pub async fn list_feed_notes(client: &Client, user_id: Uuid, from: Option<time::OffsetDateTime>, to: Option<time::OffsetDateTime>, cursor: Option<(time::OffsetDateTime, NoteId)>, limit: i64) -> Result<Vec<Note>, StorageError> {
     let mut clauses = Vec::new();
     let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
     params.push(&user_id);
     clauses.push("(n.author_id = $1 OR EXISTS (SELECT 1 FROM follows f WHERE f.follower_id = $1 AND f.followee_id = n.author_id))".to_string());
     clauses.push("NOT EXISTS (SELECT 1 FROM associations a WHERE (a.kind = 'next' AND a.to_id = n.id) OR (a.kind = 'prev' AND a.from_id = n.id))".to_string());
     clauses.push("NOT EXISTS (SELECT 1 FROM associations a WHERE a.kind = 'version' AND a.from_id = n.id)".to_string());
+    clauses.push("n.retracted_at IS NULL".to_string());
     if let Some(from_ts) = from.as_ref() { clauses.push(format!("n.created_at >= ${}", params.len() + 1)); params.push(from_ts); }
     if let Some(to_ts) = to.as_ref() { clauses.push(format!("n.created_at <= ${}", params.len() + 1)); params.push(to_ts); }
+    let cursor = cursor.map(|(created_at, note_id)| (created_at, note_id.to_bytes().to_vec()));
+    if let Some((created_at, id_bytes)) = cursor.as_ref() { clauses.push(format!("(n.created_at, n.id) < (${}, ${})", params.len() + 1, params.len() + 2)); params.push(created_at); params.push(id_bytes); }
     let limit_idx = params.len() + 1; params.push(&limit);
     let query = format!(
-        "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id FROM notes n JOIN users u ON u.user_id = n.author_id WHERE {} ORDER BY n.created_at DESC LIMIT ${}",
+        "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at FROM notes n JOIN users u ON u.user_id = n.author_id WHERE {} ORDER BY n.created_at DESC, n.id DESC LIMIT ${}",
         clauses.join(" AND "),
         limit_idx
     );
@@ -87,14 +112,24 @@ pub async fn list_feed_notes(client: &Client, user_id: Uuid, from: Option<time::
     Ok(rows.iter().map(map_note).collect())
 }
 
+pub async fn retract_notes(client: &Client, note_ids: &[NoteId]) -> Result<u64, StorageError> {
+    let ids: Vec<Vec<u8>> = note_ids.iter().map(|note_id| note_id.to_bytes().to_vec()).collect();
+    let count = client.execute(
+        "UPDATE notes SET value = ''::bytea, retracted_at = NOW() WHERE id = ANY($1) AND retracted_at IS NULL",
+        &[&ids],
+    ).await?;
+    Ok(count)
+}
+
 pub(crate) fn map_note(row: &tokio_postgres::Row) -> Note {
     let id_bytes: Vec<u8> = row.get(0); let value_bytes: Vec<u8> = row.get(1); let created_at: time::OffsetDateTime = row.get(2);
-    let author_id: Uuid = row.get(3); let email: String = row.get(4); let account_note_id = map_account_note_id(row.get(5));
+    let author_id: Uuid = row.get(3); let handle: String = row.get(4); let display_name: Option<String> = row.get(5); let account_note_id = map_account_note_id(row.get(6));
+    let retracted_at: Option<time::OffsetDateTime> = row.get(7);
     let mut id = [0u8; 32]; id.copy_from_slice(&id_bytes[..32]);
-    Note { id: encode_id(id), value: String::from_utf8_lossy(&value_bytes).to_string(), created_at: format_timestamp(created_at), author: UserProfile { user_id: author_id, email, account_note_id } }
+    Note { id: encode_id(id), value: String::from_utf8_lossy(&value_bytes).to_string(), created_at: format_timestamp(created_at), author: UserProfile { user_id: author_id, handle, display_name, account_note_id }, retracted_at: retracted_at.map(format_timestamp) }
 }
 
-fn map_account_note_id(value: Option<Vec<u8>>) -> Option<String> {
+pub(crate) fn map_account_note_id(value: Option<Vec<u8>>) -> Option<String> {
     let bytes = value?; if bytes.len() != 32 { return None; }
     let mut id = [0u8; 32]; id.copy_from_slice(&bytes[..32]); Some(encode_id(id))
 }
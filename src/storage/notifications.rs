@@ -0,0 +1,182 @@
+use tokio_postgres::{Client, GenericClient};
+use uuid::Uuid;
+
+use crate::domain::note::format_timestamp;
+use crate::domain::{Note, NoteId, Notification, UserProfile};
+use crate::storage::notes::{map_account_note_id, map_note};
+use crate::storage::StorageError;
+use crate::urls::base32::encode_id;
+
+// Retracted notes drop out of the inbox along with their notifications.
+const VISIBLE: &str = "(x.note_id IS NULL OR n.retracted_at IS NULL)";
+
+/// Tells the author of `target_id` that `note_id` replied to or quoted it, unless they are the same person.
+pub async fn insert_note_notification<C>(
+    client: &C,
+    kind: &str,
+    note_id: NoteId,
+    target_id: NoteId,
+) -> Result<(), StorageError>
+where
+    C: GenericClient + Sync,
+{
+    client
+        .execute(
+            "INSERT INTO notifications (id, user_id, kind, actor_id, note_id, target_id, created_at) \
+             SELECT uuid_generate_v4(), t.author_id, $1, f.author_id, f.id, t.id, NOW() \
+             FROM notes f JOIN notes t ON t.id = $3 \
+             WHERE f.id = $2 AND f.author_id <> t.author_id",
+            &[&kind, &note_id.to_bytes().to_vec(), &target_id.to_bytes().to_vec()],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Drops the reply or quote notification for an association that was removed.
+pub async fn delete_note_notifications<C>(
+    client: &C,
+    kind: &str,
+    note_id: NoteId,
+    target_id: NoteId,
+) -> Result<(), StorageError>
+where
+    C: GenericClient + Sync,
+{
+    client
+        .execute(
+            "DELETE FROM notifications WHERE kind = $1 AND note_id = $2 AND target_id = $3",
+            &[&kind, &note_id.to_bytes().to_vec(), &target_id.to_bytes().to_vec()],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_follow_notification<C>(
+    client: &C,
+    follower_id: Uuid,
+    followee_id: Uuid,
+) -> Result<(), StorageError>
+where
+    C: GenericClient + Sync,
+{
+    client
+        .execute(
+            "INSERT INTO notifications (id, user_id, kind, actor_id, created_at) \
+             VALUES (uuid_generate_v4(), $2, 'follow', $1, NOW())",
+            &[&follower_id, &followee_id],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Tells everyone who replied to the post `source_id` heads that `version_id` supersedes it, once
+/// per person. Replies to any segment of the post or of its earlier versions count.
+pub async fn insert_version_notifications<C>(
+    client: &C,
+    source_id: NoteId,
+    version_id: NoteId,
+) -> Result<(), StorageError>
+where
+    C: GenericClient + Sync,
+{
+    client
+        .execute(
+            "WITH RECURSIVE heads(id, author_id) AS (\
+                 SELECT id, author_id FROM notes WHERE id = $1 \
+                 UNION \
+                 SELECT p.id, h.author_id FROM heads h \
+                 JOIN associations a ON a.kind = 'version' AND a.to_id = h.id \
+                 JOIN notes p ON p.id = a.from_id AND p.author_id = h.author_id\
+             ), segments(id, author_id) AS (\
+                 SELECT id, author_id FROM heads \
+                 UNION \
+                 SELECT n.id, s.author_id FROM segments s \
+                 JOIN associations a ON (a.kind = 'next' AND a.from_id = s.id) OR (a.kind = 'prev' AND a.to_id = s.id) \
+                 JOIN notes n ON n.id = CASE WHEN a.kind = 'next' THEN a.to_id ELSE a.from_id END \
+                 WHERE n.author_id = s.author_id\
+             ) \
+             INSERT INTO notifications (id, user_id, kind, actor_id, note_id, target_id, created_at) \
+             SELECT uuid_generate_v4(), repliers.author_id, 'version', v.author_id, v.id, $1, NOW() \
+             FROM notes v CROSS JOIN ( \
+                 SELECT DISTINCT r.author_id FROM segments s \
+                 JOIN associations a ON a.kind = 'reply' AND a.to_id = s.id \
+                 JOIN notes r ON r.id = a.from_id) repliers \
+             WHERE v.id = $2 AND repliers.author_id <> v.author_id",
+            &[&source_id.to_bytes().to_vec(), &version_id.to_bytes().to_vec()],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Newest first; the actor doubles as the note author, since only they can reply, quote or version.
+pub async fn list_notifications(
+    client: &Client,
+    user_id: Uuid,
+    unread_only: bool,
+    cursor: Option<(time::OffsetDateTime, Uuid)>,
+    limit: i64,
+) -> Result<Vec<Notification>, StorageError> {
+    let (cursor_at, cursor_id) = cursor.unzip();
+    let query = format!(
+        "SELECT n.id, n.value, n.created_at, a.user_id, a.handle, a.display_name, a.account_note_id, n.retracted_at, \
+                x.id, x.kind, x.target_id, x.read_at, x.created_at \
+         FROM notifications x \
+         JOIN users a ON a.user_id = x.actor_id \
+         LEFT JOIN notes n ON n.id = x.note_id \
+         WHERE x.user_id = $1 AND {VISIBLE} AND (NOT $2 OR x.read_at IS NULL) \
+           AND ($3::timestamptz IS NULL OR (x.created_at, x.id) < ($3, $4)) \
+         ORDER BY x.created_at DESC, x.id DESC \
+         LIMIT $5"
+    );
+    let rows = client
+        .query(&query, &[&user_id, &unread_only, &cursor_at, &cursor_id, &limit])
+        .await?;
+    Ok(rows.iter().map(map_notification).collect())
+}
+
+pub async fn count_unread_notifications(client: &Client, user_id: Uuid) -> Result<i64, StorageError> {
+    let query = format!(
+        "SELECT COUNT(*) FROM notifications x LEFT JOIN notes n ON n.id = x.note_id \
+         WHERE x.user_id = $1 AND x.read_at IS NULL AND {VISIBLE}"
+    );
+    let row = client.query_one(&query, &[&user_id]).await?;
+    Ok(row.get(0))
+}
+
+/// Marks the given notifications read, or all of them without `ids`.
+pub async fn mark_notifications_read(
+    client: &Client,
+    user_id: Uuid,
+    ids: Option<&[Uuid]>,
+) -> Result<u64, StorageError> {
+    let count = client
+        .execute(
+            "UPDATE notifications SET read_at = NOW() \
+             WHERE user_id = $1 AND read_at IS NULL AND ($2::uuid[] IS NULL OR id = ANY($2))",
+            &[&user_id, &ids],
+        )
+        .await?;
+    Ok(count)
+}
+
+fn map_notification(row: &tokio_postgres::Row) -> Notification {
+    let note_id: Option<Vec<u8>> = row.get(0);
+    let note: Option<Note> = note_id.map(|_| map_note(row));
+    let target_id: Option<Vec<u8>> = row.get(10);
+    let read_at: Option<time::OffsetDateTime> = row.get(11);
+    let created_at: time::OffsetDateTime = row.get(12);
+    Notification {
+        id: row.get(8),
+        kind: row.get(9),
+        actor: UserProfile {
+            user_id: row.get(3),
+            handle: row.get(4),
+            display_name: row.get(5),
+            account_note_id: map_account_note_id(row.get(6)),
+        },
+        note,
+        target_id: target_id.and_then(|bytes| <[u8; 32]>::try_from(bytes.as_slice()).ok()).map(encode_id),
+        read: read_at.is_some(),
+        created_at: format_timestamp(created_at),
+    }
+}
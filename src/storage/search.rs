@@ -0,0 +1,64 @@
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+use crate::domain::Note;
+use crate::storage::notes::map_note;
+use crate::storage::StorageError;
+
+// Hits inside `next`/`prev` segments are walked back to their same-author chain head,
+// so each post is returned once, ranked by its best-matching segment.
+const SEARCH_CTE: &str = "WITH RECURSIVE hits AS (\
+     SELECT n.id, n.author_id, ts_rank(n.search_vector, q.query) AS rank \
+     FROM notes n, websearch_to_tsquery('simple', $1) AS q(query) \
+     WHERE n.search_vector @@ q.query\
+ ), walk AS (\
+     SELECT h.id AS hit_id, h.id AS head_id, h.author_id, h.rank, ARRAY[h.id] AS path FROM hits h \
+     UNION ALL \
+     SELECT w.hit_id, p.id, w.author_id, w.rank, w.path || p.id \
+     FROM walk w \
+     JOIN associations a ON (a.kind = 'next' AND a.to_id = w.head_id) OR (a.kind = 'prev' AND a.from_id = w.head_id) \
+     JOIN notes p ON p.id = CASE WHEN a.kind = 'next' THEN a.from_id ELSE a.to_id END \
+     WHERE p.author_id = w.author_id AND NOT p.id = ANY(w.path)\
+ ), heads AS (\
+     SELECT DISTINCT ON (hit_id) hit_id, head_id, rank FROM walk ORDER BY hit_id, cardinality(path) DESC\
+ ), ranked AS (\
+     SELECT head_id, MAX(rank) AS rank FROM heads GROUP BY head_id\
+ )";
+
+pub async fn search_notes(
+    client: &Client,
+    query: &str,
+    author: Option<Uuid>,
+    from: Option<time::OffsetDateTime>,
+    to: Option<time::OffsetDateTime>,
+    limit: i64,
+) -> Result<Vec<Note>, StorageError> {
+    let mut clauses = Vec::new();
+    let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = vec![&query];
+    if let Some(author_id) = author.as_ref() {
+        clauses.push(format!("n.author_id = ${}", params.len() + 1));
+        params.push(author_id);
+    }
+    if let Some(from_ts) = from.as_ref() {
+        clauses.push(format!("n.created_at >= ${}", params.len() + 1));
+        params.push(from_ts);
+    }
+    if let Some(to_ts) = to.as_ref() {
+        clauses.push(format!("n.created_at <= ${}", params.len() + 1));
+        params.push(to_ts);
+    }
+    let where_clause = if clauses.is_empty() {
+        "".to_string()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    let limit_idx = params.len() + 1;
+    params.push(&limit);
+    let sql = format!(
+        "{SEARCH_CTE} SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at \
+         FROM ranked r JOIN notes n ON n.id = r.head_id JOIN users u ON u.user_id = n.author_id \
+         {where_clause} ORDER BY r.rank DESC, n.created_at DESC, n.id DESC LIMIT ${limit_idx}"
+    );
+    let rows = client.query(&sql, &params).await?;
+    Ok(rows.iter().map(map_note).collect())
+}
@@ -1,50 +1,234 @@
 use tokio_postgres::Client;
 use uuid::Uuid;
 
-use crate::domain::User;
+use crate::auth::sessions::{hash_token, SessionLifetime};
+use crate::domain::note::format_timestamp;
+use crate::domain::{Session, User};
+use crate::storage::users::map_user;
 use crate::storage::StorageError;
 
+pub enum RefreshOutcome {
+    Rotated(User),
+    Reused,
+    Invalid,
+}
+
+/// Starts a new session family at sign-in and returns its family id.
 pub async fn create_session(
     client: &Client,
     user_id: Uuid,
     token: &str,
     expires_at: time::OffsetDateTime,
+) -> Result<Uuid, StorageError> {
+    let row = client
+        .query_one(
+            "INSERT INTO sessions (token_hash, user_id, expires_at, created_at) VALUES ($1, $2, $3, NOW()) RETURNING family_id",
+            &[&hash_token(token), &user_id, &expires_at],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+pub async fn create_refresh_token(
+    client: &Client,
+    user_id: Uuid,
+    family_id: Uuid,
+    token: &str,
+    expires_at: time::OffsetDateTime,
 ) -> Result<(), StorageError> {
     client
         .execute(
-            "INSERT INTO sessions (token, user_id, expires_at, created_at) VALUES ($1, $2, $3, NOW())",
-            &[&token, &user_id, &expires_at],
+            "INSERT INTO refresh_tokens (token_hash, family_id, user_id, authenticated_at, expires_at, created_at) \
+             VALUES ($1, $2, $3, NOW(), $4, NOW())",
+            &[&hash_token(token), &family_id, &user_id, &expires_at],
         )
         .await?;
     Ok(())
 }
 
+/// Looks up the session and, at most once per renew interval, slides its expiry forward
+/// without passing authenticated_at + max lifetime.
 pub async fn get_session_user(
     client: &Client,
     token: &str,
+    lifetime: SessionLifetime,
 ) -> Result<Option<User>, StorageError> {
     let row = client
         .query_opt(
-            "SELECT u.user_id, u.google_sub, u.email, u.account_note_id \
-             FROM sessions s JOIN users u ON u.user_id = s.user_id \
-             WHERE s.token = $1 AND s.expires_at > NOW()",
-            &[&token],
+            "WITH found AS (\
+                 SELECT token_hash, user_id FROM sessions WHERE token_hash = $1 AND expires_at > NOW()\
+             ), renewed AS (\
+                 UPDATE sessions s \
+                 SET expires_at = LEAST(NOW() + $2::bigint * INTERVAL '1 second', s.authenticated_at + $4::bigint * INTERVAL '1 second'), \
+                     renewed_at = NOW() \
+                 FROM found f \
+                 WHERE s.token_hash = f.token_hash AND s.renewed_at <= NOW() - $3::bigint * INTERVAL '1 second'\
+             ) \
+             SELECT u.user_id, u.email, u.account_note_id, u.handle, u.display_name \
+             FROM found f JOIN users u ON u.user_id = f.user_id \
+             WHERE u.deleted_at IS NULL",
+            &[
+                &hash_token(token),
+                &lifetime.ttl_secs,
+                &lifetime.renew_interval_secs,
+                &lifetime.max_lifetime_secs,
+            ],
         )
         .await?;
-    Ok(row.map(|r| User {
-        user_id: r.get(0),
-        google_sub: r.get(1),
-        email: r.get(2),
-        account_note_id: map_account_note_id(r.get(3)),
-    }))
+    Ok(row.map(|r| map_user(&r)))
 }
 
-fn map_account_note_id(value: Option<Vec<u8>>) -> Option<String> {
-    let bytes = value?;
-    if bytes.len() != 32 {
-        return None;
+/// Redeems a refresh token once. A second redemption revokes the whole family,
+/// since it means the token was copied.
+pub async fn rotate_refresh_token(
+    client: &mut Client,
+    refresh_token: &str,
+    new_token: &str,
+    new_refresh_token: &str,
+    lifetime: SessionLifetime,
+) -> Result<RefreshOutcome, StorageError> {
+    let transaction = client.transaction().await?;
+    let row = transaction
+        .query_opt(
+            "SELECT family_id, user_id, authenticated_at, expires_at, used_at IS NOT NULL \
+             FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE",
+            &[&hash_token(refresh_token)],
+        )
+        .await?;
+    let Some(row) = row else {
+        return Ok(RefreshOutcome::Invalid);
+    };
+    let family_id: Uuid = row.get(0);
+    let user_id: Uuid = row.get(1);
+    let authenticated_at: time::OffsetDateTime = row.get(2);
+    let expires_at: time::OffsetDateTime = row.get(3);
+    let used: bool = row.get(4);
+
+    if used {
+        transaction
+            .execute("DELETE FROM sessions WHERE family_id = $1", &[&family_id])
+            .await?;
+        transaction
+            .execute("DELETE FROM refresh_tokens WHERE family_id = $1", &[&family_id])
+            .await?;
+        transaction.commit().await?;
+        return Ok(RefreshOutcome::Reused);
+    }
+    if expires_at <= time::OffsetDateTime::now_utc() {
+        return Ok(RefreshOutcome::Invalid);
     }
-    let mut id = [0u8; 32];
-    id.copy_from_slice(&bytes[..32]);
-    Some(crate::urls::base32::encode_id(id))
+    let user_row = transaction
+        .query_opt(
+            "SELECT user_id, email, account_note_id, handle, display_name FROM users WHERE user_id = $1 AND deleted_at IS NULL",
+            &[&user_id],
+        )
+        .await?;
+    let Some(user_row) = user_row else {
+        return Ok(RefreshOutcome::Invalid);
+    };
+
+    transaction
+        .execute(
+            "UPDATE refresh_tokens SET used_at = NOW() WHERE token_hash = $1",
+            &[&hash_token(refresh_token)],
+        )
+        .await?;
+    transaction
+        .execute("DELETE FROM sessions WHERE family_id = $1", &[&family_id])
+        .await?;
+    transaction
+        .execute(
+            "INSERT INTO sessions (token_hash, user_id, expires_at, created_at, renewed_at, authenticated_at, family_id) \
+             VALUES ($1, $2, LEAST(NOW() + $3::bigint * INTERVAL '1 second', $4::timestamptz + $5::bigint * INTERVAL '1 second'), NOW(), NOW(), $4, $6)",
+            &[
+                &hash_token(new_token),
+                &user_id,
+                &lifetime.ttl_secs,
+                &authenticated_at,
+                &lifetime.max_lifetime_secs,
+                &family_id,
+            ],
+        )
+        .await?;
+    transaction
+        .execute(
+            "INSERT INTO refresh_tokens (token_hash, family_id, user_id, authenticated_at, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, NOW())",
+            &[&hash_token(new_refresh_token), &family_id, &user_id, &authenticated_at, &expires_at],
+        )
+        .await?;
+    transaction.commit().await?;
+    Ok(RefreshOutcome::Rotated(map_user(&user_row)))
+}
+
+pub async fn list_sessions(
+    client: &Client,
+    user_id: Uuid,
+    current_token: &str,
+) -> Result<Vec<Session>, StorageError> {
+    let rows = client
+        .query(
+            "SELECT id, created_at, expires_at, token_hash = $2 FROM sessions \
+             WHERE user_id = $1 AND expires_at > NOW() \
+             ORDER BY created_at DESC, id",
+            &[&user_id, &hash_token(current_token)],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|r| Session {
+            id: r.get(0),
+            created_at: format_timestamp(r.get(1)),
+            expires_at: format_timestamp(r.get(2)),
+            current: r.get(3),
+        })
+        .collect())
+}
+
+pub async fn delete_session_by_token(client: &Client, token: &str) -> Result<bool, StorageError> {
+    let row = client
+        .query_one(
+            "WITH s AS (DELETE FROM sessions WHERE token_hash = $1 RETURNING family_id), \
+             r AS (DELETE FROM refresh_tokens WHERE family_id IN (SELECT family_id FROM s)) \
+             SELECT COUNT(*) FROM s",
+            &[&hash_token(token)],
+        )
+        .await?;
+    let count: i64 = row.get(0);
+    Ok(count > 0)
+}
+
+pub async fn delete_session(
+    client: &Client,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<bool, StorageError> {
+    let row = client
+        .query_one(
+            "WITH s AS (DELETE FROM sessions WHERE id = $1 AND user_id = $2 RETURNING family_id), \
+             r AS (DELETE FROM refresh_tokens WHERE family_id IN (SELECT family_id FROM s)) \
+             SELECT COUNT(*) FROM s",
+            &[&session_id, &user_id],
+        )
+        .await?;
+    let count: i64 = row.get(0);
+    Ok(count > 0)
+}
+
+pub async fn delete_other_sessions(
+    client: &Client,
+    user_id: Uuid,
+    current_token: &str,
+) -> Result<u64, StorageError> {
+    let row = client
+        .query_one(
+            "WITH current AS (SELECT family_id FROM sessions WHERE token_hash = $2), \
+             s AS (DELETE FROM sessions WHERE user_id = $1 AND family_id NOT IN (SELECT family_id FROM current) RETURNING 1), \
+             r AS (DELETE FROM refresh_tokens WHERE user_id = $1 AND family_id NOT IN (SELECT family_id FROM current)) \
+             SELECT COUNT(*) FROM s",
+            &[&user_id, &hash_token(current_token)],
+        )
+        .await?;
+    let count: i64 = row.get(0);
+    Ok(count as u64)
 }
@@ -0,0 +1,130 @@
+use tokio_postgres::Client;
+
+use crate::domain::note::format_timestamp;
+use crate::domain::{Association, Note, NoteId, ThreadEdge};
+use crate::storage::notes::map_note;
+use crate::storage::StorageError;
+use crate::urls::base32::encode_id;
+
+// Breadth-first from the center: each note contributes its oldest $3 replies and quotes, down to
+// depth $2. A path never revisits a note. The outer LIMIT stops the walk once $4 rows exist, so
+// deep levels are dropped first.
+const DESCENDANTS_QUERY: &str = "WITH RECURSIVE walk AS (\
+     SELECT NULL::text AS kind, NULL::bytea AS parent_id, c.id, NULL::timestamptz AS linked_at, 0 AS depth, ARRAY[c.id] AS path \
+     FROM notes c WHERE c.id = $1 \
+     UNION ALL \
+     SELECT step.kind, w.id, step.from_id, step.created_at, w.depth + 1, w.path || step.from_id \
+     FROM walk w \
+     CROSS JOIN LATERAL (\
+         SELECT a.kind, a.from_id, a.created_at \
+         FROM associations a \
+         WHERE a.to_id = w.id AND a.kind IN ('reply', 'quote') AND NOT a.from_id = ANY(w.path) \
+         ORDER BY a.created_at \
+         LIMIT $3\
+     ) step \
+     WHERE w.depth < $2\
+ ), capped AS (SELECT * FROM walk LIMIT $4) \
+ SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at, \
+        w.kind, w.parent_id, w.linked_at, \
+        (SELECT COUNT(*) FROM associations r WHERE r.to_id = n.id AND r.kind IN ('reply', 'quote')) \
+ FROM capped w JOIN notes n ON n.id = w.id JOIN users u ON u.user_id = n.author_id \
+ ORDER BY w.depth, w.linked_at";
+
+// Upwards from the center, preferring the reply over a quote and then the newest association.
+const ANCESTORS_QUERY: &str = "WITH RECURSIVE walk AS (\
+     SELECT c.id, NULL::text AS kind, NULL::bytea AS child_id, NULL::timestamptz AS linked_at, 0 AS depth, ARRAY[c.id] AS path \
+     FROM notes c WHERE c.id = $1 \
+     UNION ALL \
+     SELECT step.to_id, step.kind, w.id, step.created_at, w.depth + 1, w.path || step.to_id \
+     FROM walk w \
+     CROSS JOIN LATERAL (\
+         SELECT a.kind, a.to_id, a.created_at \
+         FROM associations a \
+         WHERE a.from_id = w.id AND a.kind IN ('reply', 'quote') AND NOT a.to_id = ANY(w.path) \
+         ORDER BY a.kind = 'reply' DESC, a.created_at DESC \
+         LIMIT 1\
+     ) step \
+     WHERE w.depth < $2\
+ ) \
+ SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at, \
+        w.kind, w.child_id, w.linked_at, \
+        (SELECT COUNT(*) FROM associations r WHERE r.to_id = n.id AND r.kind IN ('reply', 'quote')) \
+ FROM walk w JOIN notes n ON n.id = w.id JOIN users u ON u.user_id = n.author_id \
+ WHERE w.depth > 0 \
+ ORDER BY w.depth DESC";
+
+/// Loads the center note with its reply count and the replies and quotes below it, shallowest first.
+pub async fn find_thread_descendants(
+    client: &Client,
+    note_id: NoteId,
+    max_depth: i32,
+    breadth: i64,
+    max_nodes: i64,
+) -> Result<Option<(Note, i64, Vec<ThreadEdge>)>, StorageError> {
+    let id_bytes = note_id.to_bytes();
+    let rows = client
+        .query(
+            DESCENDANTS_QUERY,
+            &[&id_bytes.to_vec(), &max_depth, &breadth, &max_nodes],
+        )
+        .await?;
+    let mut center = None;
+    let mut edges = Vec::new();
+    for row in &rows {
+        let kind: Option<String> = row.get(8);
+        let parent_id: Option<Vec<u8>> = row.get(9);
+        let linked_at: Option<time::OffsetDateTime> = row.get(10);
+        let note = map_note(row);
+        let reply_count: i64 = row.get(11);
+        match (kind, parent_id, linked_at) {
+            (Some(kind), Some(parent_id), Some(linked_at)) => edges.push(ThreadEdge {
+                association: Association {
+                    kind,
+                    from_id: note.id.clone(),
+                    to_id: encode_bytes(&parent_id),
+                    created_at: format_timestamp(linked_at),
+                },
+                note,
+                reply_count,
+            }),
+            _ => center = Some((note, reply_count)),
+        }
+    }
+    Ok(center.map(|(note, reply_count)| (note, reply_count, edges)))
+}
+
+/// Loads what the center replies to or quotes, transitively, ordered from the top of the thread.
+pub async fn find_thread_ancestors(
+    client: &Client,
+    note_id: NoteId,
+    max_depth: i32,
+) -> Result<Vec<ThreadEdge>, StorageError> {
+    let id_bytes = note_id.to_bytes();
+    let rows = client
+        .query(ANCESTORS_QUERY, &[&id_bytes.to_vec(), &max_depth])
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let child_id: Vec<u8> = row.get(9);
+            let linked_at: time::OffsetDateTime = row.get(10);
+            let note = map_note(row);
+            ThreadEdge {
+                association: Association {
+                    kind: row.get(8),
+                    from_id: encode_bytes(&child_id),
+                    to_id: note.id.clone(),
+                    created_at: format_timestamp(linked_at),
+                },
+                note,
+                reply_count: row.get(11),
+            }
+        })
+        .collect())
+}
+
+fn encode_bytes(bytes: &[u8]) -> String {
+    let mut id = [0u8; 32];
+    id.copy_from_slice(&bytes[..32]);
+    encode_id(id)
+}
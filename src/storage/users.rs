@@ -1,133 +1,268 @@
-use tokio_postgres::Client;
+use tokio_postgres::{Client, GenericClient};
 use uuid::Uuid;
 
-use crate::domain::{generate_note_id, Note, NoteId, User};
+use crate::domain::{default_handle, generate_note_id, Note, NoteId, User};
+use crate::storage::associations::create_association;
 use crate::storage::StorageError;
 use crate::storage::notes::map_note;
 use crate::urls::base32::encode_id;
 
+/// Signs in an identity, creating the user, the identity link and the account note in one
+/// transaction. Concurrent first sign-ins of one identity race on the identity key; the loser
+/// rolls back and returns the winner's user.
 pub async fn find_or_create_user(
-    client: &Client,
-    google_sub: &str,
+    client: &mut Client,
+    provider: &str,
+    subject: &str,
     email: &str,
 ) -> Result<User, StorageError> {
-    if let Some(mut user) = find_user_by_sub(client, google_sub).await? {
-        if user.account_note_id.is_none() {
-            let note = create_account_note(client, user.user_id, account_note_value(&user.email).as_bytes()).await?;
-            user.account_note_id = Some(note.id);
+    if let Some(user) = find_user_by_identity(&*client, provider, subject).await? {
+        if user.account_note_id.is_some() {
+            return Ok(user);
         }
-        return Ok(user);
+        return ensure_account_note(client, user).await;
     }
+    let transaction = client.transaction().await?;
     let user_id = Uuid::new_v4();
-    client
+    let handle = default_handle(user_id);
+    transaction
+        .execute(
+            "INSERT INTO users (user_id, email, handle, created_at) VALUES ($1, $2, $3, NOW())",
+            &[&user_id, &email, &handle],
+        )
+        .await?;
+    let linked = transaction
         .execute(
-            "INSERT INTO users (user_id, google_sub, email, created_at) VALUES ($1, $2, $3, NOW())",
-            &[&user_id, &google_sub, &email],
+            "INSERT INTO user_identities (provider, subject, user_id, email, created_at) VALUES ($1, $2, $3, $4, NOW()) \
+             ON CONFLICT (provider, subject) DO NOTHING",
+            &[&provider, &subject, &user_id, &email],
         )
         .await?;
-    let note = create_account_note(client, user_id, account_note_value(email).as_bytes()).await?;
+    if linked == 0 {
+        // ON CONFLICT waited for the other sign-in to commit, so its user is visible now.
+        transaction.rollback().await?;
+        let user = find_user_by_identity(&*client, provider, subject)
+            .await?
+            .ok_or_else(|| std::io::Error::other("identity_without_user"))?;
+        return ensure_account_note(client, user).await;
+    }
+    let note = insert_account_note(&transaction, user_id, account_note_value(&handle).as_bytes()).await?;
+    transaction.commit().await?;
     Ok(User {
         user_id,
-        google_sub: google_sub.to_string(),
         email: email.to_string(),
+        handle,
+        display_name: None,
         account_note_id: Some(note.id),
     })
 }
 
+/// Creates the account note for users that predate account notes, once even under concurrent
+/// sign-ins.
+async fn ensure_account_note(client: &mut Client, mut user: User) -> Result<User, StorageError> {
+    if user.account_note_id.is_some() {
+        return Ok(user);
+    }
+    let transaction = client.transaction().await?;
+    let row = transaction
+        .query_one(
+            "SELECT account_note_id FROM users WHERE user_id = $1 FOR UPDATE",
+            &[&user.user_id],
+        )
+        .await?;
+    let current: Option<Vec<u8>> = row.get(0);
+    if current.is_some() {
+        user.account_note_id = map_account_note_id(current);
+        return Ok(user);
+    }
+    let note = insert_account_note(&transaction, user.user_id, account_note_value(&user.handle).as_bytes()).await?;
+    transaction.commit().await?;
+    user.account_note_id = Some(note.id);
+    Ok(user)
+}
+
 pub async fn find_user_by_id(
     client: &Client,
     user_id: Uuid,
 ) -> Result<Option<User>, StorageError> {
     let row = client
         .query_opt(
-            "SELECT user_id, google_sub, email, account_note_id FROM users WHERE user_id = $1",
+            "SELECT user_id, email, account_note_id, handle, display_name FROM users WHERE user_id = $1 AND deleted_at IS NULL",
             &[&user_id],
         )
         .await?;
-    Ok(row.map(map_user))
+    Ok(row.as_ref().map(map_user))
 }
 
-async fn find_user_by_sub(
+pub async fn find_user_by_handle(
     client: &Client,
-    google_sub: &str,
+    handle: &str,
 ) -> Result<Option<User>, StorageError> {
     let row = client
         .query_opt(
-            "SELECT user_id, google_sub, email, account_note_id FROM users WHERE google_sub = $1",
-            &[&google_sub],
+            "SELECT user_id, email, account_note_id, handle, display_name FROM users WHERE handle = $1 AND deleted_at IS NULL",
+            &[&handle],
         )
         .await?;
-    Ok(row.map(map_user))
+    Ok(row.as_ref().map(map_user))
 }
 
-pub async fn create_account_note(
-    client: &Client,
+async fn find_user_by_identity<C>(
+    client: &C,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<User>, StorageError>
+where
+    C: GenericClient + Sync,
+{
+    let row = client
+        .query_opt(
+            "SELECT u.user_id, u.email, u.account_note_id, u.handle, u.display_name \
+             FROM user_identities i JOIN users u ON u.user_id = i.user_id \
+             WHERE i.provider = $1 AND i.subject = $2 AND u.deleted_at IS NULL",
+            &[&provider, &subject],
+        )
+        .await?;
+    Ok(row.as_ref().map(map_user))
+}
+
+/// Inserts the first account note and points the user at it; run inside a transaction.
+async fn insert_account_note<C>(
+    client: &C,
     user_id: Uuid,
     value: &[u8],
-) -> Result<Note, StorageError> {
+) -> Result<Note, StorageError>
+where
+    C: GenericClient + Sync,
+{
     let note_id = generate_note_id();
     let id_bytes = note_id.to_bytes();
-    client.batch_execute("BEGIN").await?;
-    let result: Result<Note, StorageError> = async {
-        client
-            .execute(
-                "INSERT INTO notes (id, value, created_at, author_id) VALUES ($1, $2, NOW(), $3)",
-                &[&id_bytes.to_vec(), &value, &user_id],
-            )
-            .await?;
-        client
-            .execute(
-                "UPDATE users SET account_note_id = $1 WHERE user_id = $2",
-                &[&id_bytes.to_vec(), &user_id],
-            )
-            .await?;
-        let row = client
-            .query_one(
-                "SELECT n.id, n.value, n.created_at, u.user_id, u.email, u.account_note_id \
-                 FROM notes n JOIN users u ON u.user_id = n.author_id \
-                 WHERE n.id = $1",
-                &[&id_bytes.to_vec()],
-            )
-            .await?;
-        Ok(map_note(&row))
-    }
-    .await;
-
-    match result {
-        Ok(note) => {
-            client.batch_execute("COMMIT").await?;
-            Ok(note)
-        }
-        Err(err) => {
-            client.batch_execute("ROLLBACK").await.ok();
-            Err(err)
-        }
-    }
-}
-pub async fn is_account_note_id(
-    client: &Client,
-    note_id: NoteId,
-) -> Result<bool, StorageError> {
-    let id_bytes = note_id.to_bytes();
+    client
+        .execute(
+            "INSERT INTO notes (id, value, created_at, author_id) VALUES ($1, $2, NOW(), $3)",
+            &[&id_bytes.to_vec(), &value, &user_id],
+        )
+        .await?;
+    client
+        .execute(
+            "UPDATE users SET account_note_id = $1 WHERE user_id = $2",
+            &[&id_bytes.to_vec(), &user_id],
+        )
+        .await?;
     let row = client
-        .query_opt(
-            "SELECT 1 FROM users WHERE account_note_id = $1",
+        .query_one(
+            "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at \
+             FROM notes n JOIN users u ON u.user_id = n.author_id \
+             WHERE n.id = $1",
             &[&id_bytes.to_vec()],
         )
         .await?;
-    Ok(row.is_some())
+    Ok(map_note(&row))
 }
-
-fn map_user(row: tokio_postgres::Row) -> User {
-    let account_note_id = map_account_note_id(row.get(3));
+/// Maps `user_id, email, account_note_id, handle, display_name` in that column order.
+pub(crate) fn map_user(row: &tokio_postgres::Row) -> User {
     User {
         user_id: row.get(0),
-        google_sub: row.get(1),
-        email: row.get(2),
-        account_note_id,
+        email: row.get(1),
+        account_note_id: map_account_note_id(row.get(2)),
+        handle: row.get(3),
+        display_name: row.get(4),
     }
 }
 
+/// Reads the user's current account note and share-locks the user row until commit, so a
+/// concurrent [`replace_account_note`] waits and then moves the new `author` association too.
+pub async fn lock_account_note<C>(client: &C, user_id: Uuid) -> Result<NoteId, StorageError>
+where
+    C: GenericClient + Sync,
+{
+    let row = client
+        .query_one(
+            "SELECT account_note_id FROM users WHERE user_id = $1 FOR SHARE",
+            &[&user_id],
+        )
+        .await?;
+    let account_note_id: Option<Vec<u8>> = row.get(0);
+    let bytes = account_note_id.ok_or("user has no account note")?;
+    Ok(NoteId::from_bytes(bytes.as_slice().try_into()?))
+}
+
+/// Publishes a new account note as the next version of the current one.
+/// `author` associations move to the new note so they always hang off the current account note.
+pub async fn replace_account_note(
+    client: &mut Client,
+    user_id: Uuid,
+    value: &[u8],
+) -> Result<Note, StorageError> {
+    let transaction = client.transaction().await?;
+    let row = transaction
+        .query_one(
+            "SELECT account_note_id FROM users WHERE user_id = $1 FOR UPDATE",
+            &[&user_id],
+        )
+        .await?;
+    let current: Option<Vec<u8>> = row.get(0);
+    let note_id = generate_note_id();
+    let id_bytes = note_id.to_bytes().to_vec();
+    transaction
+        .execute(
+            "INSERT INTO notes (id, value, created_at, author_id) VALUES ($1, $2, NOW(), $3)",
+            &[&id_bytes, &value, &user_id],
+        )
+        .await?;
+    if let Some(current) = current {
+        let current_id = NoteId::from_bytes(current.as_slice().try_into()?);
+        create_association(&transaction, "version", current_id, note_id).await?;
+        transaction
+            .execute(
+                "UPDATE associations SET from_id = $1 WHERE kind = 'author' AND from_id = $2",
+                &[&id_bytes, &current],
+            )
+            .await?;
+    }
+    transaction
+        .execute(
+            "UPDATE users SET account_note_id = $1 WHERE user_id = $2",
+            &[&id_bytes, &user_id],
+        )
+        .await?;
+    let row = transaction
+        .query_one(
+            "SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at \
+             FROM notes n JOIN users u ON u.user_id = n.author_id \
+             WHERE n.id = $1",
+            &[&id_bytes],
+        )
+        .await?;
+    transaction.commit().await?;
+    Ok(map_note(&row))
+}
+
+/// Lists the current account note followed by its earlier versions, newest first.
+pub async fn list_account_note_history(
+    client: &Client,
+    user_id: Uuid,
+) -> Result<Vec<Note>, StorageError> {
+    let rows = client
+        .query(
+            "WITH RECURSIVE history(id, depth) AS (\
+                 SELECT account_note_id, 0 FROM users \
+                 WHERE user_id = $1 AND deleted_at IS NULL AND account_note_id IS NOT NULL \
+                 UNION \
+                 SELECT a.from_id, h.depth + 1 FROM history h \
+                 JOIN associations a ON a.kind = 'version' AND a.to_id = h.id \
+                 JOIN notes p ON p.id = a.from_id AND p.author_id = $1 \
+                 WHERE h.depth < 1000\
+             ) \
+             SELECT n.id, n.value, n.created_at, u.user_id, u.handle, u.display_name, u.account_note_id, n.retracted_at \
+             FROM history h JOIN notes n ON n.id = h.id JOIN users u ON u.user_id = n.author_id \
+             ORDER BY h.depth",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(map_note).collect())
+}
+
 fn map_account_note_id(value: Option<Vec<u8>>) -> Option<String> {
     let bytes = value?;
     if bytes.len() != 32 {
@@ -138,6 +273,6 @@ fn map_account_note_id(value: Option<Vec<u8>>) -> Option<String> {
     Some(encode_id(id))
 }
 
-fn account_note_value(email: &str) -> String {
-    format!("account: {email}")
+fn account_note_value(handle: &str) -> String {
+    format!("account: @{handle}")
 }
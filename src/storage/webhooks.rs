@@ -0,0 +1,210 @@
+use tokio_postgres::{Client, GenericClient};
+use uuid::Uuid;
+
+use crate::domain::note::format_timestamp;
+use crate::domain::{Webhook, WebhookDelivery};
+use crate::storage::StorageError;
+
+/// A claimed outbox row with what the dispatcher needs to send it.
+pub struct DueDelivery {
+    pub id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+}
+
+pub async fn create_webhook(
+    client: &Client,
+    user_id: Uuid,
+    url: &str,
+    secret: &str,
+    events: &[String],
+) -> Result<Webhook, StorageError> {
+    let row = client
+        .query_one(
+            "INSERT INTO webhooks (id, user_id, url, secret, events, created_at) VALUES ($1, $2, $3, $4, $5, NOW()) \
+             RETURNING id, url, events, created_at",
+            &[&Uuid::new_v4(), &user_id, &url, &secret, &events],
+        )
+        .await?;
+    Ok(map_webhook(&row))
+}
+
+pub async fn list_webhooks(client: &Client, user_id: Uuid) -> Result<Vec<Webhook>, StorageError> {
+    let rows = client
+        .query(
+            "SELECT id, url, events, created_at FROM webhooks WHERE user_id = $1 ORDER BY created_at DESC, id",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(map_webhook).collect())
+}
+
+pub async fn delete_webhook(client: &Client, user_id: Uuid, webhook_id: Uuid) -> Result<bool, StorageError> {
+    let count = client
+        .execute(
+            "DELETE FROM webhooks WHERE id = $1 AND user_id = $2",
+            &[&webhook_id, &user_id],
+        )
+        .await?;
+    Ok(count > 0)
+}
+
+/// Newest deliveries first; `None` when the webhook does not exist or belongs to someone else.
+pub async fn list_webhook_deliveries(
+    client: &Client,
+    user_id: Uuid,
+    webhook_id: Uuid,
+    cursor: Option<(time::OffsetDateTime, Uuid)>,
+    limit: i64,
+) -> Result<Option<Vec<WebhookDelivery>>, StorageError> {
+    let owned = client
+        .query_opt(
+            "SELECT 1 FROM webhooks WHERE id = $1 AND user_id = $2",
+            &[&webhook_id, &user_id],
+        )
+        .await?;
+    if owned.is_none() {
+        return Ok(None);
+    }
+    let (cursor_at, cursor_id) = cursor.unzip();
+    let rows = client
+        .query(
+            "SELECT id, event, payload, status, attempts, next_attempt_at, last_status_code, last_error, delivered_at, created_at \
+             FROM webhook_deliveries \
+             WHERE webhook_id = $1 AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3)) \
+             ORDER BY created_at DESC, id DESC \
+             LIMIT $4",
+            &[&webhook_id, &cursor_at, &cursor_id, &limit],
+        )
+        .await?;
+    Ok(Some(rows.iter().map(map_delivery).collect()))
+}
+
+/// Queues one delivery per webhook owned by `owners` that subscribes to `event`.
+/// Callers pass their transaction so the outbox row commits or rolls back with the event itself.
+pub async fn enqueue_webhook_event<C>(
+    client: &C,
+    event: &str,
+    owners: &[Uuid],
+    data: serde_json::Value,
+) -> Result<u64, StorageError>
+where
+    C: GenericClient + Sync,
+{
+    let payload = serde_json::json!({
+        "event": event,
+        "created_at": format_timestamp(time::OffsetDateTime::now_utc()),
+        "data": data,
+    });
+    let count = client
+        .execute(
+            "INSERT INTO webhook_deliveries (id, webhook_id, event, payload, created_at) \
+             SELECT uuid_generate_v4(), w.id, $1, $2, NOW() FROM webhooks w \
+             WHERE w.user_id = ANY($3) AND $1 = ANY(w.events)",
+            &[&event, &payload, &owners],
+        )
+        .await?;
+    Ok(count)
+}
+
+/// Claims due deliveries and pushes their next attempt out by `lease_secs`, so a crashed sender
+/// is retried later and concurrent instances skip rows another instance holds.
+pub async fn claim_webhook_deliveries(
+    client: &Client,
+    limit: i64,
+    lease_secs: i32,
+) -> Result<Vec<DueDelivery>, StorageError> {
+    let rows = client
+        .query(
+            "UPDATE webhook_deliveries d \
+             SET attempts = d.attempts + 1, next_attempt_at = NOW() + $2::int * INTERVAL '1 second' \
+             FROM webhooks w \
+             WHERE w.id = d.webhook_id AND d.id IN ( \
+                 SELECT id FROM webhook_deliveries \
+                 WHERE status = 'pending' AND next_attempt_at <= NOW() \
+                 ORDER BY next_attempt_at \
+                 LIMIT $1 \
+                 FOR UPDATE SKIP LOCKED) \
+             RETURNING d.id, w.url, w.secret, d.event, d.payload, d.attempts",
+            &[&limit, &lease_secs],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| DueDelivery {
+            id: row.get(0),
+            url: row.get(1),
+            secret: row.get(2),
+            event: row.get(3),
+            payload: row.get(4),
+            attempts: row.get(5),
+        })
+        .collect())
+}
+
+pub async fn complete_webhook_delivery(
+    client: &Client,
+    delivery_id: Uuid,
+    status_code: i32,
+) -> Result<(), StorageError> {
+    client
+        .execute(
+            "UPDATE webhook_deliveries SET status = 'delivered', delivered_at = NOW(), last_status_code = $2, last_error = NULL \
+             WHERE id = $1",
+            &[&delivery_id, &status_code],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Records a failed attempt; without `retry_in_secs` the delivery is given up as failed.
+pub async fn fail_webhook_delivery(
+    client: &Client,
+    delivery_id: Uuid,
+    status_code: Option<i32>,
+    error: &str,
+    retry_in_secs: Option<i32>,
+) -> Result<(), StorageError> {
+    client
+        .execute(
+            "UPDATE webhook_deliveries SET last_status_code = $2, last_error = $3, \
+                 status = CASE WHEN $4::int IS NULL THEN 'failed' ELSE 'pending' END, \
+                 next_attempt_at = CASE WHEN $4::int IS NULL THEN next_attempt_at ELSE NOW() + $4::int * INTERVAL '1 second' END \
+             WHERE id = $1",
+            &[&delivery_id, &status_code, &error, &retry_in_secs],
+        )
+        .await?;
+    Ok(())
+}
+
+fn map_webhook(row: &tokio_postgres::Row) -> Webhook {
+    let created_at: time::OffsetDateTime = row.get(3);
+    Webhook {
+        id: row.get(0),
+        url: row.get(1),
+        events: row.get(2),
+        created_at: format_timestamp(created_at),
+    }
+}
+
+fn map_delivery(row: &tokio_postgres::Row) -> WebhookDelivery {
+    let status: String = row.get(3);
+    let next_attempt_at: time::OffsetDateTime = row.get(5);
+    let delivered_at: Option<time::OffsetDateTime> = row.get(8);
+    let created_at: time::OffsetDateTime = row.get(9);
+    WebhookDelivery {
+        id: row.get(0),
+        event: row.get(1),
+        payload: row.get(2),
+        next_attempt_at: (status == "pending").then(|| format_timestamp(next_attempt_at)),
+        status,
+        attempts: row.get(4),
+        last_status_code: row.get(6),
+        last_error: row.get(7),
+        delivered_at: delivered_at.map(format_timestamp),
+        created_at: format_timestamp(created_at),
+    }
+}
@@ -0,0 +1,151 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio::sync::OnceCell;
+use tokio_postgres::config::Host;
+use tokio_postgres::{Client, NoTls};
+
+use crate::auth::provider::IdentityProviders;
+use crate::config::Config;
+use crate::federation::Federation;
+use crate::realtime::Realtime;
+use crate::state::AppState;
+use crate::storage::Storage;
+
+static MIGRATED: OnceCell<()> = OnceCell::const_new();
+
+/// Settings for database-backed tests. They run against TEST_DATABASE_URL and skip when it is unset.
+pub fn config() -> Option<Config> {
+    let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return None;
+    };
+    Some(Config {
+        bind_addr: "127.0.0.1:0".to_string(),
+        database_url,
+        google_client_id: "test".to_string(),
+        google_jwks_uri: "file:///dev/null".to_string(),
+        google_issuers: vec!["https://accounts.google.com".to_string()],
+        jwks_min_refresh_secs: 60,
+        jwks_default_ttl_secs: 3600,
+        oidc_providers: Vec::new(),
+        public_base_url: "http://127.0.0.1".to_string(),
+        session_ttl_secs: 3600,
+        session_renew_interval_secs: 300,
+        session_max_lifetime_secs: 30 * 24 * 3600,
+        sweep_interval_secs: 600,
+        run_migrations: true,
+        migrations_path: "db/migrations".to_string(),
+        activitypub_enabled: true,
+        activitypub_allow_insecure: true,
+        activitypub_poll_interval_secs: 1,
+        webhook_poll_interval_secs: 1,
+        webhook_allow_insecure: true,
+    })
+}
+
+/// Connects to the test database, applying migrations once per test binary.
+pub async fn storage(config: &Config) -> Storage {
+    let storage = Storage::connect(config).await.expect("connect to TEST_DATABASE_URL");
+    MIGRATED
+        .get_or_init(|| async {
+            storage
+                .run_migrations(&config.migrations_path)
+                .await
+                .expect("apply migrations");
+        })
+        .await;
+    storage
+}
+
+/// Opens a connection that counts the statements it sends, for asserting round trips per call.
+pub async fn counting_client(config: &Config) -> (Client, Arc<AtomicUsize>) {
+    let pg: tokio_postgres::Config = config.database_url.parse().expect("parse TEST_DATABASE_URL");
+    let host = match pg.get_hosts().first() {
+        Some(Host::Tcp(host)) => host.clone(),
+        _ => panic!("TEST_DATABASE_URL needs a TCP host"),
+    };
+    let port = pg.get_ports().first().copied().unwrap_or(5432);
+    let executed = Arc::new(AtomicUsize::new(0));
+    let stream = CountingStream {
+        inner: TcpStream::connect((host.as_str(), port)).await.expect("connect to TEST_DATABASE_URL"),
+        executed: executed.clone(),
+        pending: Vec::new(),
+        started: false,
+    };
+    let (client, connection) = pg.connect_raw(stream, NoTls).await.expect("start up");
+    tokio::spawn(connection);
+    (client, executed)
+}
+
+/// Counts frontend Execute (extended protocol) and Query (simple protocol) messages.
+struct CountingStream {
+    inner: TcpStream,
+    executed: Arc<AtomicUsize>,
+    pending: Vec<u8>,
+    started: bool,
+}
+
+impl CountingStream {
+    fn scan(&mut self, written: &[u8]) {
+        self.pending.extend_from_slice(written);
+        loop {
+            // The startup message has no type byte; every later message does.
+            let header = if self.started { 5 } else { 4 };
+            if self.pending.len() < header {
+                return;
+            }
+            let length = u32::from_be_bytes(self.pending[header - 4..header].try_into().unwrap()) as usize;
+            let total = header - 4 + length;
+            if self.pending.len() < total {
+                return;
+            }
+            if self.started && matches!(self.pending[0], b'E' | b'Q') {
+                self.executed.fetch_add(1, Ordering::SeqCst);
+            }
+            self.pending.drain(..total);
+            self.started = true;
+        }
+    }
+}
+
+impl AsyncRead for CountingStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for CountingStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let written = ready!(Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.scan(&buf[..written]);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Application state over the test database, serving as `public_base_url`.
+pub async fn state(public_base_url: &str) -> Option<AppState> {
+    let mut config = config()?;
+    config.public_base_url = public_base_url.to_string();
+    let storage = storage(&config).await;
+    Some(AppState {
+        providers: IdentityProviders::from_config(&config).await,
+        federation: Federation::from_config(&config),
+        realtime: Realtime::new(),
+        storage,
+        config,
+    })
+}
@@ -1,15 +1,45 @@
-use actix_web::{web, HttpResponse};
+use actix_web::guard::GuardContext;
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
 
-use crate::api::helpers::parse_note_id;
+use crate::api::helpers::{
+    parse_note_id, parse_query, parse_query_param, parse_uuid, require_feed_user,
+};
+use crate::api::pagination::{note_cursor, notes_page, parse_cursor_param};
+use crate::domain::{normalize_handle, Note, User};
 use crate::errors::ApiError;
-use crate::related::{fetch_chain, fetch_related};
+use crate::history::{fetch_history, find_newer_revision};
+use crate::related::{fetch_chain, fetch_related, fetch_thread};
 use crate::state::AppState;
-use crate::web::note::note_html;
+use crate::web::history::history_html;
+use crate::web::note::{note_html, post_html};
+use crate::web::profile::{profile_html, ProfileView};
+use crate::web::syndication::{atom_feed, json_feed, FeedEntry, FeedMeta};
 use crate::web::render::{home_html, signin_html};
 use crate::web::templates;
 
 const FAVICON: &[u8] = include_bytes!("assets/icon_256.ico");
 const CACHE_STATIC: &str = "public, max-age=31536000, immutable";
+const PROFILE_PAGE_SIZE: usize = 20;
+const FEED_SIZE: i64 = 50;
+const THREAD_DEPTH: usize = 4;
+const THREAD_BREADTH: usize = 10;
+
+#[derive(Clone, Copy)]
+enum FeedFormat {
+    Atom,
+    Json,
+}
+
+/// Route guard that sends browsers to the HTML view of a resource that also serves JSON.
+pub fn accepts_html(ctx: &GuardContext) -> bool {
+    ctx.head()
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("text/html"))
+}
 
 pub async fn favicon() -> HttpResponse {
     HttpResponse::Ok()
@@ -56,20 +86,225 @@ pub async fn network() -> HttpResponse {
 
 pub async fn note_page(
     path: web::Path<String>,
+    req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiError<serde_json::Value>> {
     let raw = path.into_inner();
     let note_id = parse_note_id(&raw)
         .map_err(|_| ApiError::not_found("not_found", "Route not found"))?;
     let chain = fetch_chain(&state, note_id).await?;
-    let post_id = chain
-        .prev
-        .first()
-        .map(|note| note.id.as_str())
-        .unwrap_or(&chain.center.id);
-    let post_id = parse_note_id(post_id).map_err(|_| ApiError::internal())?;
+    let latest = find_newer_revision(&state, chain.head()).await?;
+    let params = parse_query(&req);
+    if let (Some(latest), Some("1" | "true")) = (&latest, parse_query_param(&params, "latest")) {
+        return Ok(HttpResponse::Found()
+            .insert_header((header::LOCATION, format!("/{}", latest.id)))
+            .finish());
+    }
+    let post_id = parse_note_id(&chain.head().id).map_err(|_| ApiError::internal())?;
     let related = fetch_related(&state, post_id).await?;
+    let thread = fetch_thread(&state, post_id, THREAD_DEPTH, THREAD_BREADTH).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(note_html(&state.config, &chain, &related.related, &thread, latest.as_ref())))
+}
+
+pub async fn history_page(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let note_id = parse_note_id(&path.into_inner()).map_err(|_| page_not_found())?;
+    let params = parse_query(&req);
+    let from = parse_query_param(&params, "from").map(parse_note_id).transpose()?;
+    let to = parse_query_param(&params, "to").map(parse_note_id).transpose()?;
+    let history = fetch_history(&state, note_id).await?;
+    let diff = history.diff(from, to)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(history_html(&state.config, &history, diff.as_ref())))
+}
+
+pub async fn profile_page(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let handle = normalize_handle(&path.into_inner()).ok_or_else(page_not_found)?;
+    let user = state
+        .storage
+        .find_user_by_handle(&handle)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(page_not_found)?;
+    render_profile(&req, &state, user).await
+}
+
+pub async fn user_page(
+    path: web::Path<String>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user_id = path.parse::<Uuid>().map_err(|_| page_not_found())?;
+    let user = state
+        .storage
+        .find_user_by_id(user_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(page_not_found)?;
+    render_profile(&req, &state, user).await
+}
+
+async fn render_profile(
+    req: &HttpRequest,
+    state: &AppState,
+    user: User,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let params = parse_query(req);
+    let cursor = note_cursor(parse_cursor_param(&params, "cursor")?)?;
+    let posts = state
+        .storage
+        .list_author_posts(user.user_id, cursor, PROFILE_PAGE_SIZE as i64 + 1)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let (followers, following) = state
+        .storage
+        .count_follows(user.user_id)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let account_note = match user.account_note_id.as_deref() {
+        Some(raw) => {
+            state
+                .storage
+                .find_note(parse_note_id(raw)?)
+                .await
+                .map_err(|_| ApiError::internal())?
+        }
+        None => None,
+    };
+    let view = ProfileView {
+        user: user.profile(),
+        account_note,
+        followers,
+        following,
+        posts: notes_page(posts, PROFILE_PAGE_SIZE),
+    };
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .body(note_html(&state.config, &chain, &related.related)))
+        .body(profile_html(&state.config, &view)))
+}
+
+fn page_not_found() -> ApiError<serde_json::Value> {
+    ApiError::not_found("not_found", "Route not found")
+}
+
+pub async fn user_feed_atom(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    user_feed(path.as_str(), &state, FeedFormat::Atom).await
+}
+
+pub async fn user_feed_json(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    user_feed(path.as_str(), &state, FeedFormat::Json).await
+}
+
+pub async fn personal_feed_atom(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    personal_feed(&req, &state, FeedFormat::Atom).await
+}
+
+pub async fn personal_feed_json(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    personal_feed(&req, &state, FeedFormat::Json).await
+}
+
+async fn user_feed(
+    raw_id: &str,
+    state: &AppState,
+    format: FeedFormat,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user_id = parse_uuid(raw_id, "invalid_user_id", "Invalid user id")?;
+    let user = state
+        .storage
+        .find_user_by_id(user_id)
+        .await
+        .map_err(|_| ApiError::internal())?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+    let notes = state
+        .storage
+        .list_author_posts(user.user_id, None, FEED_SIZE)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let base_url = &state.config.public_base_url;
+    let profile = user.profile();
+    let meta = FeedMeta {
+        title: format!("{} on lgxpkf", profile.label()),
+        home_url: format!("{base_url}/@{}", profile.handle),
+        feed_url: format!("{base_url}/users/{}/feed", profile.user_id),
+        author: Some(profile),
+    };
+    let entries = feed_entries(state, notes).await?;
+    Ok(feed_response(base_url, &meta, &entries, format, "public, max-age=300"))
+}
+
+async fn personal_feed(
+    req: &HttpRequest,
+    state: &AppState,
+    format: FeedFormat,
+) -> Result<HttpResponse, ApiError<serde_json::Value>> {
+    let user = require_feed_user(req, state).await?;
+    let notes = state
+        .storage
+        .list_feed_notes(user.user_id, None, None, None, FEED_SIZE)
+        .await
+        .map_err(|_| ApiError::internal())?;
+    let base_url = &state.config.public_base_url;
+    let meta = FeedMeta {
+        title: format!("lgxpkf feed for @{}", user.handle),
+        home_url: format!("{base_url}/"),
+        feed_url: format!("{base_url}/feed"),
+        author: None,
+    };
+    let entries = feed_entries(state, notes).await?;
+    Ok(feed_response(base_url, &meta, &entries, format, "private, max-age=300"))
+}
+
+/// Assembles each chain head into its whole post.
+async fn feed_entries(
+    state: &AppState,
+    notes: Vec<Note>,
+) -> Result<Vec<FeedEntry>, ApiError<serde_json::Value>> {
+    let mut entries = Vec::with_capacity(notes.len());
+    for note in notes {
+        let chain = fetch_chain(state, parse_note_id(&note.id)?).await?;
+        let content_html = post_html(&chain);
+        entries.push(FeedEntry { note, content_html });
+    }
+    Ok(entries)
+}
+
+fn feed_response(
+    base_url: &str,
+    meta: &FeedMeta,
+    entries: &[FeedEntry],
+    format: FeedFormat,
+    cache_control: &'static str,
+) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    response.insert_header((header::CACHE_CONTROL, cache_control));
+    match format {
+        FeedFormat::Atom => response
+            .content_type("application/atom+xml; charset=utf-8")
+            .body(atom_feed(base_url, meta, entries)),
+        FeedFormat::Json => response
+            .content_type("application/feed+json; charset=utf-8")
+            .body(json_feed(base_url, meta, entries).to_string()),
+    }
 }
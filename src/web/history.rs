@@ -0,0 +1,122 @@
+use crate::config::Config;
+use crate::history::{DiffOp, History, Revision, RevisionDiff};
+use crate::web::escape::{escape_attr, escape_html};
+use crate::web::markdown::note_excerpt;
+use crate::web::render::login_uri;
+use crate::web::templates;
+
+pub fn history_html(config: &Config, history: &History, diff: Option<&RevisionDiff>) -> String {
+    let post_id = &history.post_id;
+    let author = history
+        .revisions
+        .first()
+        .map(|revision| revision.author.label())
+        .unwrap_or_default();
+    let count = history.revisions.len();
+    let title = if count == 1 {
+        "1 revision".to_string()
+    } else {
+        format!("{count} revisions")
+    };
+    let description = format!("Version history of lgxpkf note {post_id} ({title})");
+    let history_url = format!("{}/{}/history", config.public_base_url, post_id);
+    let revision_items = render_revision_items(&history.revisions, diff);
+    let compare_form = render_compare_form(history, diff);
+    let diff_html = match diff {
+        Some(diff) => render_diff(history, diff),
+        None => "<div class=\"empty\">This post has not been edited.</div>".to_string(),
+    };
+    let base = templates::HISTORY
+        .replace("{{CLIENT_ID}}", &escape_attr(&config.google_client_id))
+        .replace("{{LOGIN_URI}}", &escape_attr(&login_uri(config)))
+        .replace("{{HISTORY_DESCRIPTION}}", &escape_attr(&description))
+        .replace("{{HISTORY_URL}}", &escape_attr(&history_url))
+        .replace("{{POST_ID}}", &escape_attr(post_id))
+        .replace("{{POST_AUTHOR}}", &escape_html(&author))
+        .replace("{{REVISION_COUNT}}", &escape_html(&title))
+        .replace("{{REVISION_ITEMS}}", "__lgxpkf_revision_items__")
+        .replace("{{COMPARE_FORM}}", "__lgxpkf_compare_form__")
+        .replace("{{DIFF}}", "__lgxpkf_diff__");
+    base.replace("__lgxpkf_revision_items__", &revision_items)
+        .replace("__lgxpkf_compare_form__", &compare_form)
+        .replace("__lgxpkf_diff__", &diff_html)
+}
+
+fn render_revision_items(revisions: &[Revision], diff: Option<&RevisionDiff>) -> String {
+    revisions
+        .iter()
+        .rev()
+        .map(|revision| {
+            let selected = diff.is_some_and(|diff| diff.from == revision.id || diff.to == revision.id);
+            let class = if selected { "revision-item selected" } else { "revision-item" };
+            let summary = if revision.retracted_at.is_some() {
+                "Retracted".to_string()
+            } else {
+                note_excerpt(&revision.value, 120)
+            };
+            format!(
+                "<a class=\"{class}\" href=\"/{}\"><span class=\"revision-number\">v{}</span><span class=\"revision-text\">{}</span><span class=\"revision-meta\">{}</span></a>",
+                escape_attr(&revision.id),
+                revision.number,
+                escape_html(&summary),
+                escape_html(&revision.created_at)
+            )
+        })
+        .collect()
+}
+
+fn render_compare_form(history: &History, diff: Option<&RevisionDiff>) -> String {
+    let Some(diff) = diff else {
+        return String::new();
+    };
+    let action = escape_attr(&format!("/{}/history", history.post_id));
+    let from = render_revision_options(&history.revisions, &diff.from);
+    let to = render_revision_options(&history.revisions, &diff.to);
+    format!(
+        "<form class=\"compare-form\" action=\"{action}\" method=\"get\"><label>From<select name=\"from\">{from}</select></label><label>To<select name=\"to\">{to}</select></label><button type=\"submit\">Compare</button></form>"
+    )
+}
+
+fn render_revision_options(revisions: &[Revision], selected: &str) -> String {
+    revisions
+        .iter()
+        .map(|revision| {
+            let attr = if revision.id == selected { " selected" } else { "" };
+            format!(
+                "<option value=\"{}\"{attr}>v{} · {}</option>",
+                escape_attr(&revision.id),
+                revision.number,
+                escape_html(&revision.created_at)
+            )
+        })
+        .collect()
+}
+
+fn render_diff(history: &History, diff: &RevisionDiff) -> String {
+    let label = |id: &str| {
+        history
+            .revision(id)
+            .map(|revision| format!("v{}", revision.number))
+            .unwrap_or_default()
+    };
+    let heading = format!("{} → {}", label(&diff.from), label(&diff.to));
+    if diff.lines.iter().all(|line| line.op == DiffOp::Equal) {
+        return format!("<div class=\"helper\">{heading}: no changes.</div>");
+    }
+    let lines: String = diff
+        .lines
+        .iter()
+        .map(|line| {
+            let (class, marker) = match line.op {
+                DiffOp::Equal => ("diff-equal", ' '),
+                DiffOp::Insert => ("diff-insert", '+'),
+                DiffOp::Delete => ("diff-delete", '-'),
+            };
+            format!(
+                "<span class=\"diff-line {class}\">{marker} {}</span>",
+                escape_html(&line.text)
+            )
+        })
+        .collect();
+    format!("<div class=\"helper\">{heading}</div><pre class=\"diff\">{lines}</pre>")
+}
@@ -1,9 +1,18 @@
 mod escape;
 mod handlers;
+mod history;
 mod markdown;
 mod note;
+mod profile;
 mod render;
+mod syndication;
 mod templates;
 
-pub use handlers::{favicon, guideline, home, network, note_page, privacy, signin, terms};
+pub use handlers::{
+    accepts_html, favicon, guideline, history_page, home, network, note_page, personal_feed_atom,
+    personal_feed_json, privacy, profile_page, signin, terms, user_feed_atom, user_feed_json,
+    user_page,
+};
+pub use markdown::render_markdown;
+pub use note::post_html;
 pub use render::redirect_html;
@@ -1,20 +1,36 @@
 use crate::config::Config;
-use crate::related::{NoteChain, RelatedEntry};
+use crate::domain::Note;
+use crate::related::{NoteChain, RelatedEntry, ThreadNode, ThreadResponse};
 use crate::web::escape::{escape_attr, escape_html};
 use crate::web::markdown::{note_excerpt, render_markdown};
 use crate::web::render::login_uri;
 use crate::web::templates;
 
-pub fn note_html(config: &Config, chain: &NoteChain, related: &[RelatedEntry]) -> String {
-    let markdown = chain_markdown(chain);
-    let body_html = render_markdown(&markdown);
+const RETRACTED_BODY: &str = "<p class=\"retracted\">This post was retracted by its author.</p>";
+
+pub fn note_html(
+    config: &Config,
+    chain: &NoteChain,
+    related: &[RelatedEntry],
+    thread: &ThreadResponse,
+    latest: Option<&Note>,
+) -> String {
+    let post_note = chain.head();
+    let retracted = post_note.is_retracted() || chain.center.is_retracted();
+    let markdown = chain.value();
+    let body_html = if retracted {
+        RETRACTED_BODY.to_string()
+    } else {
+        render_markdown(&markdown)
+    };
     let chain_items = render_chain_items(&chain.prev, &chain.next);
     let chain_summary = format!("{} prev, {} next", chain.prev.len(), chain.next.len());
-    let post_note = chain.prev.first().unwrap_or(&chain.center);
     let post_id_raw = &post_note.id;
     let post_id = escape_attr(post_id_raw);
     let related_items = render_related_items(related, post_id_raw);
     let version_section = render_version_section(related, post_id_raw);
+    let latest_banner = render_latest_banner(latest, post_id_raw);
+    let thread_section = render_thread(thread);
     let has_newer_version = related
         .iter()
         .any(|entry| entry.association.kind == "version" && entry.association.from_id == *post_id_raw);
@@ -26,7 +42,7 @@ pub fn note_html(config: &Config, chain: &NoteChain, related: &[RelatedEntry]) -
     let author_id = escape_attr(&chain.center.author.user_id.to_string());
     let post_author_id = escape_attr(&post_note.author.user_id.to_string());
     let account_note_id = escape_attr(chain.center.author.account_note_id.as_deref().unwrap_or(""));
-    let note_description = escape_attr(&note_excerpt(&chain.center.value, 160));
+    let note_description = escape_attr(&note_summary(&chain.center, 160));
     let note_url = escape_attr(&format!("{}/{}", config.public_base_url, note_id_raw));
     let base = templates::NOTE
         .replace("{{CLIENT_ID}}", &client_id)
@@ -34,39 +50,45 @@ pub fn note_html(config: &Config, chain: &NoteChain, related: &[RelatedEntry]) -
         .replace("{{NOTE_ID}}", &note_id)
         .replace("{{POST_ID}}", &post_id)
         .replace("{{NOTE_CREATED_AT}}", &escape_html(&chain.center.created_at))
-        .replace("{{NOTE_AUTHOR}}", &escape_html(&chain.center.author.email))
+        .replace("{{NOTE_AUTHOR}}", &escape_html(&chain.center.author.label()))
+        .replace("{{NOTE_AUTHOR_HANDLE}}", &escape_attr(&chain.center.author.handle))
         .replace("{{NOTE_AUTHOR_ID}}", &author_id)
         .replace("{{POST_AUTHOR_ID}}", &post_author_id)
         .replace("{{NOTE_ACCOUNT_NOTE_ID}}", &account_note_id)
         .replace("{{NOTE_HAS_NEWER_VERSION}}", has_newer_version)
+        .replace("{{NOTE_RETRACTED}}", if retracted { "true" } else { "false" })
         .replace("{{CHAIN_SUMMARY}}", &escape_html(&chain_summary))
         .replace("{{NOTE_DESCRIPTION}}", &note_description)
         .replace("{{NOTE_URL}}", &note_url)
+        .replace("{{LATEST_BANNER}}", "__lgxpkf_latest_banner__")
         .replace("{{VERSION_SECTION}}", "__lgxpkf_version_section__")
         .replace("{{CHAIN_ITEMS}}", "__lgxpkf_chain_items__")
         .replace("{{RELATED_ITEMS}}", "__lgxpkf_related_items__")
+        .replace("{{THREAD}}", "__lgxpkf_thread__")
         .replace("{{NOTE_BODY}}", "__lgxpkf_note_body__")
         .replace("{{NOTE_RAW}}", "__lgxpkf_note_raw__");
     base.replace("__lgxpkf_chain_items__", &chain_items)
+        .replace("__lgxpkf_latest_banner__", &latest_banner)
         .replace("__lgxpkf_version_section__", &version_section)
         .replace("__lgxpkf_related_items__", &related_items)
+        .replace("__lgxpkf_thread__", &thread_section)
         .replace("__lgxpkf_note_body__", &body_html)
         .replace("__lgxpkf_note_raw__", &escape_html(&markdown))
 }
 
-fn chain_markdown(chain: &NoteChain) -> String {
-    let mut parts = Vec::new();
-    for note in &chain.prev {
-        parts.push(note.value.as_str());
-    }
-    parts.push(chain.center.value.as_str());
-    for note in &chain.next {
-        parts.push(note.value.as_str());
+/// The whole post as HTML, for feeds and federation.
+pub fn post_html(chain: &NoteChain) -> String {
+    render_markdown(&chain.value())
+}
+
+fn note_summary(note: &Note, max_len: usize) -> String {
+    if note.is_retracted() {
+        return "Retracted note".to_string();
     }
-    parts.join("\n\n")
+    note_excerpt(&note.value, max_len)
 }
 
-fn render_chain_items(prev: &[crate::domain::Note], next: &[crate::domain::Note]) -> String {
+fn render_chain_items(prev: &[Note], next: &[Note]) -> String {
     if prev.is_empty() && next.is_empty() {
         return "<div class=\"empty\">No chained notes.</div>".to_string();
     }
@@ -76,9 +98,9 @@ fn render_chain_items(prev: &[crate::domain::Note], next: &[crate::domain::Note]
     items.join("")
 }
 
-fn render_chain_item(note: &crate::domain::Note, label: &str) -> String {
+fn render_chain_item(note: &Note, label: &str) -> String {
     let note_id = escape_attr(&note.id);
-    let summary = escape_html(&note_excerpt(&note.value, 120));
+    let summary = escape_html(&note_summary(note, 120));
     let label = escape_html(label);
     format!(
         "<a class=\"chain-item\" href=\"/{note_id}\"><span class=\"chain-label\">{label}</span><span class=\"chain-text\">{summary}</span></a>"
@@ -99,7 +121,7 @@ fn render_related_items(related: &[RelatedEntry], center_id: &str) -> String {
 
 fn render_related_item(entry: &RelatedEntry, center_id: &str) -> String {
     let note_id = escape_attr(&entry.note.id);
-    let summary = escape_html(&note_excerpt(&entry.note.value, 120));
+    let summary = escape_html(&note_summary(&entry.note, 120));
     let created = escape_html(&entry.note.created_at);
     let kind = escape_html(&association_label(&entry.association, center_id));
     let citation = escape_html(&entry.note.id);
@@ -126,6 +148,91 @@ fn render_version_section(related: &[RelatedEntry], center_id: &str) -> String {
     )
 }
 
+fn render_latest_banner(latest: Option<&Note>, post_id: &str) -> String {
+    let Some(latest) = latest else {
+        return String::new();
+    };
+    let note_id = escape_attr(&latest.id);
+    let created = escape_html(&latest.created_at);
+    let compare = escape_attr(&format!("/{post_id}/history?from={post_id}&to={}", latest.id));
+    format!(
+        "<section class=\"latest-banner\" role=\"status\"><div><strong>This post has been updated.</strong> You are viewing an older revision; the latest is from {created}.</div><div class=\"latest-actions\"><a href=\"{compare}\">What changed</a><a class=\"latest-link\" href=\"/{note_id}\">View latest version</a></div></section>"
+    )
+}
+
+fn render_thread(thread: &ThreadResponse) -> String {
+    if thread.ancestors.is_empty() && thread.replies.is_empty() {
+        return "<div class=\"empty\">No replies yet.</div>".to_string();
+    }
+    let context: String = thread
+        .ancestors
+        .iter()
+        .map(|entry| {
+            let label = if entry.association.kind == "quote" { "Quoting" } else { "In reply to" };
+            render_thread_item(&entry.note, label, "thread-parent")
+        })
+        .collect();
+    let context = if context.is_empty() {
+        String::new()
+    } else {
+        format!("<div class=\"thread-context\">{context}</div>")
+    };
+    let mut tree = render_thread_nodes(&thread.replies, 1);
+    let hidden = thread.reply_count - thread.replies.len() as i64;
+    if hidden > 0 && !thread.replies.is_empty() {
+        tree.push_str(&format!(
+            "<div class=\"helper\">{hidden} more not shown.</div>"
+        ));
+    }
+    format!("{context}{tree}")
+}
+
+fn render_thread_nodes(nodes: &[ThreadNode], depth: usize) -> String {
+    if nodes.is_empty() {
+        return String::new();
+    }
+    let items: String = nodes
+        .iter()
+        .map(|node| {
+            let label = if node.kind == "quote" { "Quote" } else { "Reply" };
+            let item = render_thread_item(&node.note, label, "thread-item");
+            let hidden = node.reply_count - node.replies.len() as i64;
+            let more = if hidden > 0 {
+                format!(
+                    "<a class=\"thread-more\" href=\"/{}\">{hidden} more</a>",
+                    escape_attr(&node.note.id)
+                )
+            } else {
+                String::new()
+            };
+            let branch = if node.replies.is_empty() {
+                more
+            } else {
+                // Only the first level starts expanded; deeper branches open on demand.
+                let open = if depth == 1 { " open" } else { "" };
+                let count = node.reply_count;
+                let noun = if count == 1 { "reply" } else { "replies" };
+                format!(
+                    "<details class=\"thread-branch\"{open}><summary>{count} {noun}</summary>{}{more}</details>",
+                    render_thread_nodes(&node.replies, depth + 1)
+                )
+            };
+            format!("<li class=\"thread-node\">{item}{branch}</li>")
+        })
+        .collect();
+    format!("<ul class=\"thread\">{items}</ul>")
+}
+
+fn render_thread_item(note: &Note, label: &str, class: &str) -> String {
+    let note_id = escape_attr(&note.id);
+    let summary = escape_html(&note_summary(note, 160));
+    let author = escape_html(&note.author.label());
+    let created = escape_html(&note.created_at);
+    format!(
+        "<a class=\"{class}\" href=\"/{note_id}\"><span class=\"related-kind\">{label}</span><span class=\"related-text\">{summary}</span><span class=\"related-meta\">{author} · {created}</span></a>"
+    )
+}
+
 fn association_label(association: &crate::domain::Association, center_id: &str) -> String {
     match association.kind.as_str() {
         "version" => {
@@ -0,0 +1,90 @@
+use crate::config::Config;
+use crate::domain::{Note, Page, UserProfile};
+use crate::web::escape::{escape_attr, escape_html};
+use crate::web::markdown::{note_excerpt, render_markdown};
+use crate::web::render::login_uri;
+use crate::web::templates;
+
+pub struct ProfileView {
+    pub user: UserProfile,
+    pub account_note: Option<Note>,
+    pub followers: i64,
+    pub following: i64,
+    pub posts: Page<Note>,
+}
+
+pub fn profile_html(config: &Config, view: &ProfileView) -> String {
+    let user = &view.user;
+    let name = user.display_name.as_deref().unwrap_or(&user.handle);
+    let title = format!("{} on lgxpkf", user.label());
+    let profile_url = format!("{}/@{}", config.public_base_url, user.handle);
+    let feed_url = format!("{}/users/{}/feed", config.public_base_url, user.user_id);
+    let description = profile_description(view);
+    let account_note = match view.account_note.as_ref() {
+        Some(note) if !note.is_retracted() => format!(
+            "{}<div class=\"pager\"><a href=\"/{}\">History</a></div>",
+            render_markdown(&note.value),
+            escape_attr(&note.id)
+        ),
+        _ => "<div class=\"empty\">No account note.</div>".to_string(),
+    };
+    let post_items = render_post_items(&view.posts.items);
+    let pager = render_pager(&user.handle, view.posts.next_cursor.as_deref());
+    let base = templates::PROFILE
+        .replace("{{CLIENT_ID}}", &escape_attr(&config.google_client_id))
+        .replace("{{LOGIN_URI}}", &escape_attr(&login_uri(config)))
+        .replace("{{PROFILE_TITLE}}", &escape_attr(&title))
+        .replace("{{PROFILE_DESCRIPTION}}", &escape_attr(&description))
+        .replace("{{PROFILE_URL}}", &escape_attr(&profile_url))
+        .replace("{{PROFILE_FEED_URL}}", &escape_attr(&feed_url))
+        .replace("{{PROFILE_HANDLE}}", &escape_attr(&user.handle))
+        .replace("{{PROFILE_NAME}}", &escape_html(name))
+        .replace("{{PROFILE_USER_ID}}", &escape_attr(&user.user_id.to_string()))
+        .replace("{{FOLLOWER_COUNT}}", &view.followers.to_string())
+        .replace("{{FOLLOWING_COUNT}}", &view.following.to_string())
+        .replace("{{ACCOUNT_NOTE}}", "__lgxpkf_account_note__")
+        .replace("{{POST_ITEMS}}", "__lgxpkf_post_items__")
+        .replace("{{PAGER}}", "__lgxpkf_pager__");
+    base.replace("__lgxpkf_account_note__", &account_note)
+        .replace("__lgxpkf_post_items__", &post_items)
+        .replace("__lgxpkf_pager__", &pager)
+}
+
+fn profile_description(view: &ProfileView) -> String {
+    let counts = format!("{} followers, {} following", view.followers, view.following);
+    match view.account_note.as_ref() {
+        Some(note) if !note.is_retracted() => {
+            format!("{} ({counts})", note_excerpt(&note.value, 140))
+        }
+        _ => format!("{} on lgxpkf ({counts})", view.user.label()),
+    }
+}
+
+fn render_post_items(posts: &[Note]) -> String {
+    if posts.is_empty() {
+        return "<div class=\"empty\">No posts yet.</div>".to_string();
+    }
+    posts.iter().map(render_post_item).collect()
+}
+
+fn render_post_item(note: &Note) -> String {
+    let note_id = escape_attr(&note.id);
+    let summary = if note.is_retracted() {
+        "Retracted note".to_string()
+    } else {
+        note_excerpt(&note.value, 120)
+    };
+    let summary = escape_html(&summary);
+    let created = escape_html(&note.created_at);
+    format!(
+        "<a class=\"chain-item\" href=\"/{note_id}\"><span class=\"chain-label\">{created}</span><span class=\"chain-text\">{summary}</span></a>"
+    )
+}
+
+fn render_pager(handle: &str, next_cursor: Option<&str>) -> String {
+    let Some(cursor) = next_cursor else {
+        return String::new();
+    };
+    let href = escape_attr(&format!("/@{handle}?cursor={cursor}"));
+    format!("<div class=\"pager\"><a href=\"{href}\">Older posts</a></div>")
+}
@@ -0,0 +1,101 @@
+use serde_json::json;
+
+use crate::domain::{Note, UserProfile};
+use crate::web::escape::escape_html;
+use crate::web::markdown::note_excerpt;
+
+/// A chain head with its whole post rendered to HTML.
+pub struct FeedEntry {
+    pub note: Note,
+    pub content_html: String,
+}
+
+pub struct FeedMeta {
+    pub title: String,
+    pub home_url: String,
+    /// Feed URL without the .atom / .json extension.
+    pub feed_url: String,
+    pub author: Option<UserProfile>,
+}
+
+/// Stable entry id: independent of the public base URL.
+fn entry_id(note: &Note) -> String {
+    format!("urn:lgxpkf:note:{}", note.id)
+}
+
+fn entry_title(note: &Note) -> String {
+    let first_line = note.value.lines().find(|line| !line.trim().is_empty()).unwrap_or("");
+    note_excerpt(first_line.trim_start_matches('#').trim(), 80)
+}
+
+pub fn atom_feed(base_url: &str, meta: &FeedMeta, entries: &[FeedEntry]) -> String {
+    let updated = entries
+        .first()
+        .map(|entry| entry.note.created_at.as_str())
+        .unwrap_or("1970-01-01T00:00:00Z");
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("<id>{}</id>\n", escape_html(&format!("{}.atom", meta.feed_url))));
+    out.push_str(&format!("<title>{}</title>\n", escape_html(&meta.title)));
+    out.push_str(&format!("<updated>{}</updated>\n", escape_html(updated)));
+    out.push_str(&format!(
+        "<link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>\n",
+        escape_html(&format!("{}.atom", meta.feed_url))
+    ));
+    out.push_str(&format!("<link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n", escape_html(&meta.home_url)));
+    out.push_str("<generator>lgxpkf</generator>\n");
+    for entry in entries {
+        let note = &entry.note;
+        let url = format!("{base_url}/{}", note.id);
+        let author = &note.author;
+        out.push_str("<entry>\n");
+        out.push_str(&format!("<id>{}</id>\n", escape_html(&entry_id(note))));
+        out.push_str(&format!("<title>{}</title>\n", escape_html(&entry_title(note))));
+        out.push_str(&format!("<link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n", escape_html(&url)));
+        out.push_str(&format!("<published>{}</published>\n", escape_html(&note.created_at)));
+        out.push_str(&format!("<updated>{}</updated>\n", escape_html(&note.created_at)));
+        out.push_str(&format!(
+            "<author><name>{}</name><uri>{}</uri></author>\n",
+            escape_html(&author.label()),
+            escape_html(&format!("{base_url}/@{}", author.handle))
+        ));
+        out.push_str(&format!("<content type=\"html\">{}</content>\n", escape_html(&entry.content_html)));
+        out.push_str("</entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+pub fn json_feed(base_url: &str, meta: &FeedMeta, entries: &[FeedEntry]) -> serde_json::Value {
+    let author_json = |author: &UserProfile| {
+        json!({
+            "name": author.label(),
+            "url": format!("{base_url}/@{}", author.handle),
+        })
+    };
+    let items: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|entry| {
+            let note = &entry.note;
+            json!({
+                "id": entry_id(note),
+                "url": format!("{base_url}/{}", note.id),
+                "title": entry_title(note),
+                "content_html": entry.content_html,
+                "date_published": note.created_at,
+                "authors": [author_json(&note.author)],
+            })
+        })
+        .collect();
+    let mut feed = json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": meta.title,
+        "home_page_url": meta.home_url,
+        "feed_url": format!("{}.json", meta.feed_url),
+        "items": items,
+    });
+    if let Some(author) = meta.author.as_ref() {
+        feed["authors"] = json!([author_json(author)]);
+    }
+    feed
+}
@@ -1,8 +1,10 @@
 pub const HOME: &str = include_str!("home.html");
 pub const NOTE: &str = include_str!("note.html");
+pub const HISTORY: &str = include_str!("history.html");
 pub const SIGNIN: &str = include_str!("signin.html");
 pub const TERMS: &str = include_str!("terms.html");
 pub const PRIVACY: &str = include_str!("privacy.html");
 pub const GUIDELINE: &str = include_str!("guideline.html");
 pub const NETWORK: &str = include_str!("network.html");
+pub const PROFILE: &str = include_str!("profile.html");
 pub const REDIRECT: &str = include_str!("redirect.html");
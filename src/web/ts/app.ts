@@ -1,10 +1,19 @@
-import { apiJsonDecoded } from "./shared/api";
+import { apiJson, apiJsonDecoded } from "./shared/api";
 import { getById } from "./shared/dom";
 import { readStorage, writeStorage } from "./shared/storage";
-import { decodeAuthUser } from "./shared/types";
+import { decodeAuthUser, decodeInbox, decodeUnreadCount, InboxItem } from "./shared/types";
 
 type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
 
+const INBOX_POLL_MS = 60000;
+
+const inboxText: Record<string, string> = {
+  reply: "replied to your note",
+  quote: "quoted your note",
+  follow: "followed you",
+  version: "posted a new version of a note you replied to",
+};
+
 (() => {
   const storageKey = "lgxpkf.session";
   const state: SessionState = { token: readStorage(storageKey), user: null };
@@ -15,6 +24,13 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
   const accountMenu = getById<HTMLDivElement>("account-menu");
   const accountEmail = getById<HTMLDivElement>("account-email");
   const signoutBtn = getById<HTMLButtonElement>("account-signout");
+  const signoutAllBtn = getById<HTMLButtonElement>("account-signout-all");
+  const notifyToggle = getById<HTMLButtonElement>("notify-toggle");
+  const notifyBadge = getById<HTMLSpanElement>("notify-badge");
+  const notifyMenu = getById<HTMLDivElement>("notify-menu");
+  const notifyList = getById<HTMLDivElement>("notify-list");
+  const notifyReadAll = getById<HTMLButtonElement>("notify-read-all");
+  let inboxTimer: number | null = null;
 
   const shortLabel = (value: string): string =>
     value.length > 26 ? `${value.slice(0, 12)}...${value.slice(-8)}` : value;
@@ -31,16 +47,107 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
     accountToggle.setAttribute("aria-expanded", open ? "true" : "false");
   };
 
+  const setInboxOpen = (open: boolean): void => {
+    if (!notifyMenu || !notifyToggle) return;
+    notifyMenu.hidden = !open;
+    notifyToggle.setAttribute("aria-expanded", open ? "true" : "false");
+  };
+
+  const setUnreadCount = (count: number): void => {
+    if (!notifyBadge) return;
+    notifyBadge.textContent = count > 99 ? "99+" : String(count);
+    notifyBadge.hidden = count === 0;
+  };
+
+  const inboxLink = (item: InboxItem): string => {
+    if (item.note) return `/${item.note.id}`;
+    return `/@${encodeURIComponent(item.actor.handle)}`;
+  };
+
+  const markRead = async (ids: string[] | null): Promise<void> => {
+    if (!state.token) return;
+    const payload = await apiJson("/notifications/read", state.token, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: ids ? JSON.stringify({ ids }) : undefined,
+      keepalive: true,
+    });
+    const count = decodeUnreadCount(payload);
+    if (count !== null) setUnreadCount(count);
+  };
+
+  const renderInbox = (items: InboxItem[]): void => {
+    if (!notifyList) return;
+    notifyList.replaceChildren();
+    if (items.length === 0) {
+      const empty = document.createElement("div");
+      empty.className = "notify-empty";
+      empty.textContent = "Nothing new yet.";
+      notifyList.appendChild(empty);
+      return;
+    }
+    items.forEach((item) => {
+      const link = document.createElement("a");
+      link.className = item.read ? "notify-item" : "notify-item unread";
+      link.href = inboxLink(item);
+      const text = document.createElement("div");
+      text.textContent = `@${item.actor.handle} ${inboxText[item.kind] || item.kind}`;
+      const time = document.createElement("div");
+      time.className = "notify-time";
+      time.textContent = new Date(item.createdAt).toLocaleString();
+      link.append(text, time);
+      if (!item.read) {
+        link.addEventListener("click", () => {
+          // Navigation may cut the request short; keepalive lets it finish.
+          void markRead([item.id]).catch(() => undefined);
+        });
+      }
+      notifyList.appendChild(link);
+    });
+  };
+
+  const loadInbox = async (): Promise<void> => {
+    if (!state.token) return;
+    try {
+      const inbox = await apiJsonDecoded("/notifications?limit=20", state.token, decodeInbox);
+      if (!inbox) return;
+      setUnreadCount(inbox.unreadCount);
+      renderInbox(inbox.items);
+    } catch (_) {
+      // A failed poll keeps the last badge; the next one retries.
+    }
+  };
+
+  const startInbox = (): void => {
+    if (inboxTimer !== null) return;
+    void loadInbox();
+    inboxTimer = window.setInterval(() => {
+      if (document.visibilityState === "visible") void loadInbox();
+    }, INBOX_POLL_MS);
+  };
+
+  const stopInbox = (): void => {
+    if (inboxTimer !== null) window.clearInterval(inboxTimer);
+    inboxTimer = null;
+    setUnreadCount(0);
+  };
+
   const setSignedIn = (signedIn: boolean): void => {
     document.body.dataset.signedIn = signedIn ? "true" : "false";
     if (signinLink) signinLink.hidden = signedIn;
     if (postLink) postLink.hidden = !signedIn;
     if (accountToggle) accountToggle.hidden = !signedIn;
-    if (!signedIn) setMenuOpen(false);
+    if (notifyToggle) notifyToggle.hidden = !signedIn;
+    if (signedIn) startInbox();
+    if (!signedIn) {
+      setMenuOpen(false);
+      setInboxOpen(false);
+      stopInbox();
+    }
     if (signedIn && state.user) {
-      const label = state.user.email || "Account";
+      const label = `@${state.user.handle}`;
       if (accountLabel) accountLabel.textContent = shortLabel(label);
-      if (accountEmail) accountEmail.textContent = label;
+      if (accountEmail) accountEmail.textContent = state.user.email || label;
     }
   };
 
@@ -84,31 +191,72 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
     accountToggle.addEventListener("click", (event) => {
       event.stopPropagation();
       const open = accountMenu ? accountMenu.hidden : false;
+      setInboxOpen(false);
       setMenuOpen(Boolean(open));
     });
   }
 
+  if (notifyToggle) {
+    notifyToggle.addEventListener("click", (event) => {
+      event.stopPropagation();
+      const open = notifyMenu ? notifyMenu.hidden : false;
+      setMenuOpen(false);
+      setInboxOpen(Boolean(open));
+      if (open) void loadInbox();
+    });
+  }
+
+  if (notifyReadAll) {
+    notifyReadAll.addEventListener("click", () => {
+      void markRead(null)
+        .then(loadInbox)
+        .catch(() => undefined);
+    });
+  }
+
+  const signOut = async (everywhere: boolean): Promise<void> => {
+    const token = state.token;
+    if (token) {
+      try {
+        if (everywhere) await apiJson("/auth/sessions", token, { method: "DELETE" });
+        await apiJson("/auth/logout", token, { method: "POST" });
+      } catch (_) {
+        // The local session is cleared regardless; an expired token needs no revocation.
+      }
+    }
+    clearSession();
+    setSignedIn(false);
+    dispatchSession();
+    window.location.assign("/");
+  };
+
   if (signoutBtn) {
     signoutBtn.addEventListener("click", () => {
-      clearSession();
-      setSignedIn(false);
-      dispatchSession();
-      window.location.assign("/");
+      void signOut(false);
+    });
+  }
+
+  if (signoutAllBtn) {
+    signoutAllBtn.addEventListener("click", () => {
+      void signOut(true);
     });
   }
 
   document.addEventListener("click", (event) => {
+    const target = event.target as Node;
+    if (notifyMenu && !notifyMenu.hidden && !notifyMenu.contains(target)) {
+      if (!notifyToggle || !notifyToggle.contains(target)) setInboxOpen(false);
+    }
     if (!accountMenu || accountMenu.hidden) return;
-    if (
-      accountMenu.contains(event.target as Node) ||
-      (accountToggle && accountToggle.contains(event.target as Node))
-    ) {
+    if (accountMenu.contains(target) || (accountToggle && accountToggle.contains(target))) {
       return;
     }
     setMenuOpen(false);
   });
 
   document.addEventListener("keydown", (event) => {
-    if (event.key === "Escape") setMenuOpen(false);
+    if (event.key !== "Escape") return;
+    setMenuOpen(false);
+    setInboxOpen(false);
   });
 })();
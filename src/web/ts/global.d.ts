@@ -12,7 +12,9 @@ declare global {
 
   interface LgxpkfUserProfile {
     user_id: string;
-    email: string;
+    handle: string;
+    display_name?: string | null;
+    email?: string;
     account_note_id?: string | null;
   }
 
@@ -26,5 +28,6 @@ declare global {
     value: string;
     created_at: string;
     author?: LgxpkfUserProfile | null;
+    retracted_at?: string | null;
   }
 }
@@ -1,26 +1,33 @@
 import { apiJson } from "./shared/api";
 import { getById, isTypingTarget, setMessage, setModalState } from "./shared/dom";
 import { bindNoteCardActions, renderNoteCard } from "./shared/note_cards";
+import { readSearchQuery, runSearch } from "./shared/search";
 import { readStorage } from "./shared/storage";
-import { decodeNotes } from "./shared/types";
+import { openEventStream } from "./shared/stream";
+import { decodeNotes, decodeStreamNote } from "./shared/types";
 
 type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
 
 (() => {
   const state: SessionState = { token: readStorage("lgxpkf.session"), user: null };
-  const guestHero = getById<HTMLElement>("guest-hero"), randomBlock = getById<HTMLElement>("random-block"), randomList = getById<HTMLElement>("random-list"), randomStatus = getById<HTMLElement>("random-status"), timelineBlock = getById<HTMLElement>("timeline-block"), timelineTitle = getById<HTMLElement>("timeline-title"), timelineList = getById<HTMLElement>("timeline-list"), timelineStatus = getById<HTMLElement>("timeline-status"), composer = getById<HTMLElement>("composer"), postLink = getById<HTMLAnchorElement>("post-link"), closeBtn = getById<HTMLButtonElement>("close-composer"), noteForm = getById<HTMLFormElement>("note-form"), noteValue = getById<HTMLTextAreaElement>("note-value"), noteStatus = getById<HTMLElement>("note-status"), submitBtn = getById<HTMLButtonElement>("submit");
+  const guestHero = getById<HTMLElement>("guest-hero"), randomBlock = getById<HTMLElement>("random-block"), randomList = getById<HTMLElement>("random-list"), randomStatus = getById<HTMLElement>("random-status"), timelineBlock = getById<HTMLElement>("timeline-block"), timelineTitle = getById<HTMLElement>("timeline-title"), timelineList = getById<HTMLElement>("timeline-list"), timelineStatus = getById<HTMLElement>("timeline-status"), composer = getById<HTMLElement>("composer"), postLink = getById<HTMLAnchorElement>("post-link"), closeBtn = getById<HTMLButtonElement>("close-composer"), noteForm = getById<HTMLFormElement>("note-form"), noteValue = getById<HTMLTextAreaElement>("note-value"), noteStatus = getById<HTMLElement>("note-status"), submitBtn = getById<HTMLButtonElement>("submit"), searchBlock = getById<HTMLElement>("search-block"), searchTitle = getById<HTMLElement>("search-title"), searchList = getById<HTMLElement>("search-list"), searchStatus = getById<HTMLElement>("search-status"), searchInput = getById<HTMLInputElement>("search-query");
+  const searchQuery = readSearchQuery();
 
   const setSignedIn = (signedIn: boolean): void => {
     document.body.dataset.signedIn = signedIn ? "true" : "false";
     if (submitBtn) submitBtn.disabled = !signedIn;
     if (noteValue) noteValue.disabled = !signedIn;
     if (guestHero) guestHero.hidden = signedIn;
-    if (randomBlock) randomBlock.hidden = signedIn;
-    if (timelineBlock) timelineBlock.hidden = !signedIn;
+    if (randomBlock) randomBlock.hidden = signedIn || Boolean(searchQuery);
+    if (timelineBlock) timelineBlock.hidden = !signedIn || Boolean(searchQuery);
+    if (searchBlock) searchBlock.hidden = !searchQuery;
   };
 
   if (randomList) bindNoteCardActions(randomList);
   if (timelineList) bindNoteCardActions(timelineList);
+  if (searchList) bindNoteCardActions(searchList);
+  if (searchInput) searchInput.value = searchQuery;
+  if (searchTitle && searchQuery) searchTitle.textContent = `Search: ${searchQuery}`;
 
   const loadList = async (path: string, list: HTMLElement | null, status: HTMLElement | null, emptyText: string): Promise<void> => {
     if (!list || !status) return;
@@ -63,6 +70,26 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
     }
   };
 
+  let stopFeedStream: (() => void) | null = null;
+
+  const prependFeedNote = (payload: unknown): void => {
+    const update = decodeStreamNote(payload);
+    if (!update || !timelineList) return;
+    if (update.supersedes) timelineList.querySelector(`[data-note-id="${update.supersedes}"]`)?.remove();
+    if (timelineList.querySelector(`[data-note-id="${update.note.id}"]`)) return;
+    timelineList.prepend(renderNoteCard(update.note));
+    if (timelineStatus) timelineStatus.textContent = "";
+  };
+
+  const startFeedStream = (): void => {
+    stopFeedStream?.();
+    stopFeedStream = state.token && !searchQuery
+      ? openEventStream("/feed/stream", state.token, (event, data) => { if (event === "note") prependFeedNote(data); }, () => void loadFeed())
+      : null;
+  };
+
+  const loadHome = (): Promise<void> => (searchQuery ? runSearch(searchQuery, state.token, searchList, searchStatus) : state.token ? loadFeed() : loadRandom());
+
   let composeIntent = new URLSearchParams(window.location.search).get("compose") === "1";
   if (composeIntent && window.history.replaceState) window.history.replaceState({}, "", window.location.pathname);
 
@@ -87,15 +114,15 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
     state.token = session.token;
     state.user = session.user;
     setSignedIn(Boolean(state.token));
-    if (state.token) void loadFeed();
-    else void loadRandom();
+    void loadHome();
+    startFeedStream();
     maybeOpenComposer();
   };
 
   const bootstrap = (): void => {
     setSignedIn(Boolean(state.token));
-    if (state.token) void loadFeed();
-    else void loadRandom();
+    void loadHome();
+    startFeedStream();
     maybeOpenComposer();
   };
 
@@ -41,7 +41,7 @@ import { decodeNotes, decodeRelatedResponse } from "./shared/types";
     const height = Math.max(canvas.clientHeight, 160);
     const node = {
       id: note.id,
-      label: note.author?.email || "note",
+      label: note.author ? `@${note.author.handle}` : "note",
       x: rand(60, width - 60),
       y: rand(60, height - 60),
       vx: 0,
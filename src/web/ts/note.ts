@@ -1,7 +1,8 @@
 import { apiJson, apiJsonDecoded } from "./shared/api";
 import { escapeHtml, getById, setMessage, setModalState } from "./shared/dom";
 import { readStorage } from "./shared/storage";
-import { decodeFollowUserIds, decodePostNote } from "./shared/types";
+import { openEventStream } from "./shared/stream";
+import { decodeFollowCheck, decodePostNote, decodeStreamAssociation } from "./shared/types";
 
 type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
 
@@ -9,20 +10,21 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
   const state: SessionState = { token: readStorage("lgxpkf.session"), user: null };
   const noteId = document.body.dataset.noteId || "", postId = document.body.dataset.postId || noteId, noteAuthorId = document.body.dataset.authorId || "", postAuthorId = document.body.dataset.postAuthorId || noteAuthorId, accountNoteId = document.body.dataset.accountNoteId || "";
   let hasNewerVersion = document.body.dataset.hasNewerVersion === "true";
+  const retracted = document.body.dataset.retracted === "true";
   const editBtn = getById<HTMLButtonElement>("edit-note"), editor = getById<HTMLElement>("editor"), editForm = getById<HTMLFormElement>("edit-form"), editValue = getById<HTMLTextAreaElement>("edit-value"), editStatus = getById<HTMLElement>("edit-status"), closeEditor = getById<HTMLButtonElement>("close-editor"), relatedList = getById<HTMLElement>("related-list"), versionCard = getById<HTMLElement>("version-card"), versionList = getById<HTMLElement>("version-list"), copyBtn = getById<HTMLButtonElement>("copy-link"), copyJsonBtn = getById<HTMLButtonElement>("copy-json"), copyStatus = getById<HTMLElement>("copy-status"), followToggle = getById<HTMLButtonElement>("follow-toggle"), followStatus = getById<HTMLElement>("follow-status"), linkForm = getById<HTMLFormElement>("link-form"), linkTarget = getById<HTMLInputElement>("link-target"), linkKind = getById<HTMLInputElement>("link-kind"), linkStatus = getById<HTMLElement>("link-status");
   const blockedKinds = new Set(["author"]);
 
   const isAccountNote = (): boolean => Boolean(accountNoteId && accountNoteId === postId);
   const isOwner = (): boolean => Boolean(state.user && postAuthorId && state.user.user_id === postAuthorId);
-  const canEdit = (): boolean => Boolean(state.token) && isOwner() && !isAccountNote() && Boolean(postId);
-  const canLink = (): boolean => Boolean(state.token) && isOwner() && !isAccountNote() && Boolean(postId);
+  const canEdit = (): boolean => Boolean(state.token) && isOwner() && !retracted && Boolean(postId);
+  const canLink = (): boolean => Boolean(state.token) && isOwner() && !isAccountNote() && !retracted && Boolean(postId);
   const canCreateVersion = (): boolean => canEdit() && !hasNewerVersion;
   const editLockMessage = (): string => {
     if (canCreateVersion()) return "";
     if (!postId) return "Missing post id.";
     if (!state.token) return "Sign in at /signin.";
     if (!isOwner()) return "Only the author can edit this note.";
-    if (isAccountNote()) return "Editing disabled for this note.";
+    if (retracted) return "Editing disabled for this note.";
     if (hasNewerVersion) return "Newer version already exists.";
     return "Editing disabled for this note.";
   };
@@ -68,15 +70,40 @@ type SessionState = { token: string | null; user: LgxpkfUserProfile | null };
     }
   };
 
+  const insertRelatedItem = (label: string, note: LgxpkfNote): void => {
+    const id = escapeHtml(note.id || "");
+    if (!id || !relatedList || relatedList.querySelector(`a[href="/${id}"]`)) return;
+    relatedList.querySelector(".empty")?.remove();
+    const item = document.createElement("a");
+    item.className = "related-item";
+    item.href = `/${id}`;
+    item.innerHTML = `<span class="related-kind">${escapeHtml(label)}</span><span class="related-text">${escapeHtml(note.value || "")}</span><span class="related-meta">${escapeHtml(note.created_at || "")}</span><span class="related-cite">Citation: ${id}</span>`;
+    relatedList.prepend(item);
+  };
+
+  const applyAssociation = (payload: unknown): void => {
+    const update = decodeStreamAssociation(payload);
+    if (!update) return;
+    if (update.kind === "version") {
+      insertVersionItem(update.note);
+      hasNewerVersion = true;
+      setSignedIn(Boolean(state.token));
+      return;
+    }
+    insertRelatedItem(update.kind === "reply" ? "Reply from" : update.kind, update.note);
+  };
+
+  if (postId) openEventStream(`/notes/${postId}/stream`, null, (event, data) => { if (event === "association") applyAssociation(data); });
+
   const loadFollowState = async (): Promise<void> => {
     if (!followToggle || !followStatus) return;
     if (!state.token || !state.user) { followToggle.disabled = true; followStatus.textContent = "Sign in to follow."; return; }
     if (!noteAuthorId || state.user.user_id === noteAuthorId) { followToggle.disabled = true; followStatus.textContent = ""; return; }
     followToggle.disabled = true; followStatus.textContent = "Checking follow...";
     try {
-      const payload = await apiJson(`/follows?user=${state.user.user_id}&direction=following`, state.token);
-      const followIds = decodeFollowUserIds(payload);
-      const following = followIds.includes(noteAuthorId);
+      const payload = await apiJson(`/follows/check?follower=${state.user.user_id}&followee=${noteAuthorId}`, state.token);
+      const following = decodeFollowCheck(payload);
+      if (following === null) throw new Error("Follow check failed.");
       followToggle.dataset.following = following ? "true" : "false";
       followToggle.textContent = following ? "Unfollow" : "Follow";
       followStatus.textContent = following ? "Following." : "Not following.";
@@ -1,4 +1,5 @@
 import { escapeHtml } from "./dom";
+import { profileLabel } from "./types";
 
 const notePath = (id: string): string => `/${encodeURIComponent(id)}`;
 
@@ -36,7 +37,7 @@ const shareNote = async (id: string, button: HTMLButtonElement | null): Promise<
 export const renderNoteCard = (note: LgxpkfNote): HTMLElement => {
   const id = note.id || "";
   const card = document.createElement("article");
-  const author = escapeHtml(note.author?.email || "Unknown");
+  const author = escapeHtml(note.author ? profileLabel(note.author) : "Unknown");
   const created = escapeHtml(note.created_at || "");
   const value = escapeHtml(note.value || "");
   card.className = "note";
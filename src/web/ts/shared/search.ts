@@ -0,0 +1,26 @@
+import { apiJson } from "./api";
+import { renderNoteCard } from "./note_cards";
+import { decodeNotes } from "./types";
+
+export const readSearchQuery = (): string =>
+  (new URLSearchParams(window.location.search).get("q") || "").trim();
+
+export const searchPath = (query: string): string => `/search?q=${encodeURIComponent(query)}`;
+
+export const runSearch = async (
+  query: string,
+  token: string | null,
+  list: HTMLElement | null,
+  status: HTMLElement | null,
+): Promise<void> => {
+  if (!list || !status) return;
+  status.textContent = "Searching...";
+  try {
+    const items = decodeNotes(await apiJson(searchPath(query), token));
+    list.innerHTML = "";
+    status.textContent = items.length ? "" : "No matching posts.";
+    items.forEach((note) => list.appendChild(renderNoteCard(note)));
+  } catch (err) {
+    status.textContent = err instanceof Error ? err.message : "Search failed.";
+  }
+};
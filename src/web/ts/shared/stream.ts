@@ -0,0 +1,70 @@
+type StreamHandler = (event: string, data: unknown) => void;
+
+const RETRY_MS = 3000;
+
+const parseFrame = (frame: string): { event: string; data: string } | null => {
+  let event = "message";
+  const data: string[] = [];
+  frame.split("\n").forEach((line) => {
+    if (!line || line.startsWith(":")) return;
+    const colon = line.indexOf(":");
+    const field = colon < 0 ? line : line.slice(0, colon);
+    const value = colon < 0 ? "" : line.slice(colon + 1).replace(/^ /, "");
+    if (field === "event") event = value;
+    else if (field === "data") data.push(value);
+  });
+  return data.length ? { event, data: data.join("\n") } : null;
+};
+
+// EventSource cannot send the session token, so streams are read with fetch. Reconnects until stopped;
+// onReconnect runs after every reconnect so callers can reload whatever they missed meanwhile.
+export const openEventStream = (path: string, token: string | null, onEvent: StreamHandler, onReconnect?: () => void): (() => void) => {
+  let stopped = false;
+  let opened = false;
+  let controller: AbortController | null = null;
+
+  const readStream = async (): Promise<boolean> => {
+    controller = new AbortController();
+    const headers = new Headers({ Accept: "text/event-stream" });
+    if (token) headers.set("Authorization", `Bearer ${token}`);
+    const response = await fetch(path, { headers, signal: controller.signal });
+    if (response.status >= 400 && response.status < 500) return false;
+    if (!response.ok || !response.body) return true;
+    if (opened) onReconnect?.();
+    opened = true;
+    const reader = response.body.getReader();
+    const decoder = new TextDecoder();
+    let buffer = "";
+    for (;;) {
+      const { done, value } = await reader.read();
+      if (done) return true;
+      buffer += decoder.decode(value, { stream: true });
+      let boundary = buffer.indexOf("\n\n");
+      while (boundary >= 0) {
+        const frame = parseFrame(buffer.slice(0, boundary));
+        buffer = buffer.slice(boundary + 2);
+        boundary = buffer.indexOf("\n\n");
+        if (!frame) continue;
+        try {
+          onEvent(frame.event, JSON.parse(frame.data));
+        } catch (_) {
+          continue;
+        }
+      }
+    }
+  };
+
+  const run = async (): Promise<void> => {
+    while (!stopped) {
+      const retry = await readStream().catch(() => true);
+      if (!retry || stopped) return;
+      await new Promise((resolve) => window.setTimeout(resolve, RETRY_MS));
+    }
+  };
+
+  void run();
+  return () => {
+    stopped = true;
+    controller?.abort();
+  };
+};
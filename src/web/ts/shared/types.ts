@@ -11,9 +11,14 @@ const isOptionalString = (value: unknown): value is string | null | undefined =>
 export const isUserProfile = (value: unknown): value is LgxpkfUserProfile =>
   isRecord(value) &&
   isString(value.user_id) &&
-  isString(value.email) &&
+  isString(value.handle) &&
+  (!("display_name" in value) || isOptionalString(value.display_name)) &&
+  (!("email" in value) || isString(value.email)) &&
   (!("account_note_id" in value) || isOptionalString(value.account_note_id));
 
+export const profileLabel = (profile: LgxpkfUserProfile): string =>
+  profile.display_name ? `${profile.display_name} (@${profile.handle})` : `@${profile.handle}`;
+
 export const isNote = (value: unknown): value is LgxpkfNote =>
   isRecord(value) &&
   isString(value.id) &&
@@ -46,14 +51,9 @@ export const decodePostNote = (payload: unknown): LgxpkfNote | null => {
   return isNote(root) ? root : null;
 };
 
-export const decodeFollowUserIds = (payload: unknown): string[] => {
-  if (!isRecord(payload)) return [];
-  const edges = payload.edges;
-  if (!Array.isArray(edges)) return [];
-  return edges.flatMap((edge) => {
-    if (!isRecord(edge) || !isUserProfile(edge.user)) return [];
-    return [edge.user.user_id];
-  });
+export const decodeFollowCheck = (payload: unknown): boolean | null => {
+  if (!isRecord(payload) || typeof payload.following !== "boolean") return null;
+  return payload.following;
 };
 
 export type RelatedResponse = { center: LgxpkfNote; related: LgxpkfNote[] };
@@ -67,3 +67,57 @@ export const decodeRelatedResponse = (payload: unknown): RelatedResponse | null
   });
   return { center: payload.center, related: notes };
 };
+
+export type StreamNote = { note: LgxpkfNote; supersedes: string | null };
+
+export const decodeStreamNote = (payload: unknown): StreamNote | null => {
+  if (!isRecord(payload) || !isNote(payload.note)) return null;
+  return { note: payload.note, supersedes: isString(payload.supersedes) ? payload.supersedes : null };
+};
+
+export type StreamAssociation = { kind: string; fromId: string; toId: string; note: LgxpkfNote };
+
+export const decodeStreamAssociation = (payload: unknown): StreamAssociation | null => {
+  if (!isRecord(payload) || !isNote(payload.note) || !isRecord(payload.association)) return null;
+  const { kind, from_id: fromId, to_id: toId } = payload.association;
+  if (!isString(kind) || !isString(fromId) || !isString(toId)) return null;
+  return { kind, fromId, toId, note: payload.note };
+};
+
+export type InboxItem = {
+  id: string;
+  kind: string;
+  actor: LgxpkfUserProfile;
+  note: LgxpkfNote | null;
+  targetId: string | null;
+  read: boolean;
+  createdAt: string;
+};
+
+export type Inbox = { items: InboxItem[]; unreadCount: number };
+
+export const decodeInbox = (payload: unknown): Inbox | null => {
+  if (!isRecord(payload) || typeof payload.unread_count !== "number") return null;
+  const items = Array.isArray(payload.items) ? payload.items : [];
+  return {
+    unreadCount: payload.unread_count,
+    items: items.flatMap((item) => {
+      if (!isRecord(item) || !isString(item.id) || !isString(item.kind)) return [];
+      if (!isUserProfile(item.actor) || !isString(item.created_at)) return [];
+      return [
+        {
+          id: item.id,
+          kind: item.kind,
+          actor: item.actor,
+          note: isNote(item.note) ? item.note : null,
+          targetId: isString(item.target_id) ? item.target_id : null,
+          read: item.read === true,
+          createdAt: item.created_at,
+        },
+      ];
+    }),
+  };
+};
+
+export const decodeUnreadCount = (payload: unknown): number | null =>
+  isRecord(payload) && typeof payload.unread_count === "number" ? payload.unread_count : null;
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use sha2::Sha256;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::dispatch::{spawn_dispatcher, Claimed, Outbox};
+use crate::outbound;
+use crate::storage::{DueDelivery, Storage, StorageError};
+
+/// Sends queued webhook deliveries every `interval_secs` until `shutdown` flips. Endpoints that
+/// resolve to private addresses are refused at connect time unless `allow_private` is set.
+pub fn spawn_webhook_dispatcher(
+    storage: Storage,
+    interval_secs: u64,
+    allow_private: bool,
+    shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    let builder = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(Policy::none())
+        .user_agent(concat!("lgxpkf-webhooks/", env!("CARGO_PKG_VERSION")));
+    let client = outbound::guard(builder, allow_private).build().unwrap_or_default();
+    spawn_dispatcher(WebhookOutbox { client, storage }, interval_secs, shutdown)
+}
+
+#[derive(Clone)]
+struct WebhookOutbox {
+    client: Client,
+    storage: Storage,
+}
+
+impl Claimed for DueDelivery {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn attempts(&self) -> i32 {
+        self.attempts
+    }
+}
+
+impl Outbox for WebhookOutbox {
+    type Delivery = DueDelivery;
+
+    const NAME: &'static str = "webhook";
+
+    async fn claim(&self, limit: i64, lease_secs: i32) -> Result<Vec<DueDelivery>, StorageError> {
+        self.storage.claim_webhook_deliveries(limit, lease_secs).await
+    }
+
+    async fn send(&self, delivery: &DueDelivery) -> Result<u16, String> {
+        let body = delivery.payload.to_string();
+        let timestamp = time::OffsetDateTime::now_utc().unix_timestamp();
+        let signature = sign_payload(&delivery.secret, timestamp, body.as_bytes());
+        let response = self
+            .client
+            .post(&delivery.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header("X-Lgxpkf-Event", &delivery.event)
+            .header("X-Lgxpkf-Delivery", delivery.id.to_string())
+            .header("X-Lgxpkf-Signature", format!("t={timestamp},v1={signature}"))
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(response.status().as_u16())
+    }
+
+    async fn complete(&self, id: Uuid, status_code: i32) -> Result<(), StorageError> {
+        self.storage.complete_webhook_delivery(id, status_code).await
+    }
+
+    async fn fail(
+        &self,
+        id: Uuid,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_secs: Option<i32>,
+    ) -> Result<(), StorageError> {
+        self.storage
+            .fail_webhook_delivery(id, status_code, error, retry_in_secs)
+            .await
+    }
+}
+
+/// Hex HMAC-SHA256 over `{timestamp}.{body}`; receivers recompute it with the webhook secret.
+fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    HEXLOWER.encode(&mac.finalize().into_bytes())
+}